@@ -1,5 +1,6 @@
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::Instant;
 
 use eyre::{Result, WrapErr};
 use glium::Surface;
@@ -10,12 +11,14 @@ use glium::glutin::display::GetGlDisplay;
 use glium::glutin::prelude::*;
 use glium::glutin::surface::{SurfaceAttributesBuilder, SwapInterval, WindowSurface};
 use glutin_winit::DisplayBuilder;
+use log::LevelFilter;
 use raw_window_handle::HasWindowHandle;
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowId};
 
+use crate::logs;
 use crate::visual::gui::ui::Ui;
 use crate::visual::render::glium_renderer::GliumRenderer;
 use crate::visual::vis::Vis;
@@ -90,7 +93,8 @@ impl<F: FnMut(&mut Ui<'_>) -> Result<()>> ApplicationHandler for App<F> {
         let scr_sz = window.inner_size().to_logical::<f64>(scale).into();
         let vis = Vis::new(scale, scr_sz).unwrap();
         let rend = GliumRenderer::new(&disp).unwrap();
-        self.ctx = Some(Ctx { disp, win: window, vis, rend });
+        window.request_redraw();
+        self.ctx = Some(Ctx { disp, win: window, vis, rend, redraw_at: None });
     }
 
     fn window_event(
@@ -125,10 +129,24 @@ impl<F: FnMut(&mut Ui<'_>) -> Result<()>> ApplicationHandler for App<F> {
                 if let Err(e) = t.finish() {
                     log::error!("Error finishing frame: {e}");
                 }
+                // Only draw again without input if the frame asked for it.
+                ctx.redraw_at = ctx.vis.io().redraw_at;
             }
-            _ => {}
+            _ => ctx.win.request_redraw(),
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(ctx) = self.ctx.as_mut() else { return };
+        match ctx.redraw_at {
+            Some(t) if t <= Instant::now() => {
+                ctx.redraw_at = None;
+                ctx.win.request_redraw();
+                event_loop.set_control_flow(ControlFlow::Wait);
+            }
+            Some(t) => event_loop.set_control_flow(ControlFlow::WaitUntil(t)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
         }
-        ctx.win.request_redraw();
     }
 }
 
@@ -137,9 +155,16 @@ pub struct Ctx {
     win: Arc<Window>,
     vis: Vis,
     rend: GliumRenderer,
+    redraw_at: Option<Instant>,
 }
 
+const LOG_CAP: usize = 1000;
+
 pub fn run(f: impl FnMut(&mut Ui<'_>) -> Result<()> + 'static) -> Result<()> {
+    // Capture logs to show in the UI, unless the app installed its own logger.
+    if logs::init(LOG_CAP, LevelFilter::Info).is_err() {
+        log::info!("logger already installed, logs won't be shown in the UI");
+    }
     let event_loop = EventLoop::new().unwrap();
     let mut app = App { ctx: None, f };
     event_loop.run_app(&mut app).wrap_err("blew up in event loop")
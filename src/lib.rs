@@ -41,4 +41,5 @@
 pub mod any;
 pub mod ctx;
 pub mod errors;
+pub mod logs;
 pub mod visual;
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+use eyre::{Result, eyre};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// A captured log record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    // Time since logging started.
+    pub time: Duration,
+    pub level: Level,
+    pub target: String,
+    pub msg: String,
+}
+
+impl LogEntry {
+    // Whether this is at least as severe as |level|, its target contains
+    // |target| and its message contains |search|, ignoring case.
+    #[must_use]
+    pub fn matches(&self, level: LevelFilter, target: &str, search: &str) -> bool {
+        let has =
+            |s: &str, sub: &str| sub.is_empty() || s.to_lowercase().contains(&sub.to_lowercase());
+        self.level <= level && has(&self.target, target) && has(&self.msg, search)
+    }
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let t = self.time.as_secs_f64();
+        write!(f, "{t:9.3} {:<5} {}: {}", self.level, self.target, self.msg)
+    }
+}
+
+// The most recent log records, oldest first.
+#[derive(Debug)]
+pub struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    cap: usize,
+    // Records ever pushed, so viewers can tell when new ones arrive.
+    pushed: u64,
+}
+
+impl LogBuffer {
+    #[must_use]
+    pub fn new(cap: usize) -> Self {
+        Self { entries: VecDeque::with_capacity(cap), cap: cap.max(1), pushed: 0 }
+    }
+
+    pub fn push(&mut self, e: LogEntry) {
+        if self.entries.len() == self.cap {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(e);
+        self.pushed += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[must_use]
+    pub fn pushed(&self) -> u64 {
+        self.pushed
+    }
+
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+}
+
+static LOGS: OnceLock<Mutex<LogBuffer>> = OnceLock::new();
+
+// Keeps records in the shared buffer.
+#[derive(Debug)]
+struct CaptureLogger {
+    start: Instant,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, m: &Metadata<'_>) -> bool {
+        m.level() <= log::max_level()
+    }
+
+    fn log(&self, r: &Record<'_>) {
+        let Some(buf) = LOGS.get().filter(|_| self.enabled(r.metadata())) else {
+            return;
+        };
+        let e = LogEntry {
+            time: self.start.elapsed(),
+            level: r.level(),
+            target: r.target().to_owned(),
+            msg: r.args().to_string(),
+        };
+        buf.lock().unwrap_or_else(PoisonError::into_inner).push(e);
+    }
+
+    fn flush(&self) {}
+}
+
+// Installs a logger which keeps the last |cap| records at |level| or above,
+// for `LogConsole` to show. Fails if a logger is already installed, in which
+// case nothing is captured.
+pub fn init(cap: usize, level: LevelFilter) -> Result<()> {
+    log::set_boxed_logger(Box::new(CaptureLogger { start: Instant::now() }))
+        .map_err(|e| eyre!("could not install logger: {e}"))?;
+    // Only one logger can be installed, so this is the first time here.
+    LOGS.get_or_init(|| Mutex::new(LogBuffer::new(cap)));
+    log::set_max_level(level);
+    Ok(())
+}
+
+// The captured records, if `init` installed the logger. Don't log while
+// holding this.
+#[must_use]
+pub fn captured() -> Option<MutexGuard<'static, LogBuffer>> {
+    LOGS.get().map(|b| b.lock().unwrap_or_else(PoisonError::into_inner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, target: &str, msg: &str) -> LogEntry {
+        LogEntry { time: Duration::ZERO, level, target: target.to_owned(), msg: msg.to_owned() }
+    }
+
+    #[test]
+    fn test_buffer() {
+        let mut b = LogBuffer::new(2);
+        for m in ["a", "b", "c"] {
+            b.push(entry(Level::Info, "t", m));
+        }
+        assert_eq!(b.len(), 2);
+        assert_eq!(b.pushed(), 3);
+        assert_eq!(b.iter().map(|e| e.msg.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+        b.clear();
+        assert!(b.is_empty());
+        assert_eq!(b.pushed(), 3);
+    }
+
+    #[test]
+    fn test_matches() {
+        let e = entry(Level::Warn, "memevis::render", "Texture Missing");
+        assert!(e.matches(LevelFilter::Warn, "", ""));
+        assert!(e.matches(LevelFilter::Trace, "RENDER", "texture m"));
+        assert!(!e.matches(LevelFilter::Error, "", ""));
+        assert!(!e.matches(LevelFilter::Trace, "gui", ""));
+        assert!(!e.matches(LevelFilter::Trace, "", "font"));
+    }
+}
@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
 use derive_more::Display;
-use lyon::math::Translation;
+use lyon::math::{Angle, Transform};
 use lyon::path::Path;
 use num_traits::Zero;
 use serde::{Deserialize, Serialize};
@@ -48,33 +48,99 @@ pub type GblLayer = Layer<GblType>;
 pub type PrtLayer = Layer<PrtType>;
 pub type LclLayer = Layer<LclType>;
 
-#[derive(Debug, Default, PartialEq, Copy, Clone)]
+// Linear part of a transform as [xx, xy, yx, yy], mapping (x, y) to
+// (xx * x + xy * y, yx * x + yy * y).
+pub type Mat = [f64; 4];
+
+const IDENTITY: Mat = [1.0, 0.0, 0.0, 1.0];
+
+// |a| applied after |b|.
+fn mul(a: Mat, b: Mat) -> Mat {
+    [
+        a[0] * b[0] + a[1] * b[2],
+        a[0] * b[1] + a[1] * b[3],
+        a[2] * b[0] + a[3] * b[2],
+        a[2] * b[1] + a[3] * b[3],
+    ]
+}
+
+// Affine map from space |S| to space |T|, plus a z offset. Rects are mapped
+// to the rect bounding them, so they're only exact without rotation or skew.
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub struct LayerTf<S, T> {
+    m: Mat,
     off: Pt2D<f64, T>,
     z: ZOrder<T>,
     _s: PhantomData<S>,
     _t: PhantomData<T>,
 }
 
+impl<S: Basic, T: Basic> Default for LayerTf<S, T> {
+    fn default() -> Self {
+        Self::new(Pt2D::zero(), ZOrder::zero())
+    }
+}
+
 impl<S: Basic, T: Basic> LayerTf<S, T> {
     #[must_use]
     pub const fn new(off: Pt2D<f64, T>, z: ZOrder<T>) -> Self {
-        Self { off, z, _s: PhantomData, _t: PhantomData }
+        Self::affine(IDENTITY, off, z)
+    }
+
+    #[must_use]
+    pub const fn affine(m: Mat, off: Pt2D<f64, T>, z: ZOrder<T>) -> Self {
+        Self { m, off, z, _s: PhantomData, _t: PhantomData }
+    }
+
+    // Scales by |sx| and |sy| about the origin of |S| before this transform.
+    #[must_use]
+    pub fn scale(&self, sx: f64, sy: f64) -> Self {
+        Self { m: mul(self.m, [sx, 0.0, 0.0, sy]), ..*self }
+    }
+
+    // Rotates by |a| about the origin of |S| before this transform. Positive
+    // angles turn clockwise on screen, since y points down.
+    #[must_use]
+    pub fn rotate(&self, a: Angle) -> Self {
+        let (sin, cos) = f64::from(a.radians).sin_cos();
+        Self { m: mul(self.m, [cos, -sin, sin, cos]), ..*self }
     }
 
+    // Skews by |ax| along x and |ay| along y before this transform.
+    #[must_use]
+    pub fn skew(&self, ax: Angle, ay: Angle) -> Self {
+        let (tx, ty) = (f64::from(ax.radians).tan(), f64::from(ay.radians).tan());
+        Self { m: mul(self.m, [1.0, tx, ty, 1.0]), ..*self }
+    }
+
+    // This transform followed by |tf|.
     #[must_use]
     pub fn concat<U: Basic>(&self, tf: &LayerTf<T, U>) -> LayerTf<S, U> {
-        LayerTf::new(self.off.coerce() + tf.off, tf.z(self.z))
+        LayerTf::affine(mul(tf.m, self.m), tf.pt(self.off), tf.z(self.z))
     }
 
     #[must_use]
     pub const fn coerce<U: Basic, V: Basic>(&self) -> LayerTf<U, V> {
-        LayerTf::new(self.off.coerce(), self.z.coerce())
+        LayerTf::affine(self.m, self.off.coerce(), self.z.coerce())
     }
 
+    // Inverse transform. Transforms which flatten space, e.g. a scale of zero,
+    // have no inverse and give non-finite results.
     #[must_use]
     pub fn inv(&self) -> LayerTf<T, S> {
-        LayerTf::new(-self.off.coerce(), ZOrder::new(-self.z.z))
+        let [xx, xy, yx, yy] = self.m;
+        let det = xx * yy - xy * yx;
+        let m = [yy / det, -xy / det, -yx / det, xx / det];
+        let (x, y) = (self.off.x, self.off.y);
+        let off = Pt2D::new(-(m[0] * x + m[1] * y), -(m[2] * x + m[3] * y));
+        LayerTf::affine(m, off, ZOrder::new(-self.z.z))
+    }
+
+    // How much lengths are scaled on average, e.g. for line widths. This is
+    // the square root of the change in area.
+    #[must_use]
+    pub fn avg_scale(&self) -> f64 {
+        (self.m[0] * self.m[3] - self.m[1] * self.m[2]).abs().sqrt()
     }
 
     #[must_use]
@@ -84,18 +150,24 @@ impl<S: Basic, T: Basic> LayerTf<S, T> {
 
     #[must_use]
     pub fn pt(&self, p: Pt2D<f64, S>) -> Pt2D<f64, T> {
-        Pt2D::new(p.x + self.off.x, p.y + self.off.y)
+        let [xx, xy, yx, yy] = self.m;
+        Pt2D::new(xx * p.x + xy * p.y + self.off.x, yx * p.x + yy * p.y + self.off.y)
     }
 
+    // Size of the rect bounding a rect of size |sz| once transformed.
     #[must_use]
-    #[allow(clippy::unused_self)]
     pub fn sz(&self, sz: Sz2D<f64, S>) -> Sz2D<f64, T> {
-        Sz2D::new(sz.w, sz.h)
+        let [xx, xy, yx, yy] = self.m;
+        Sz2D::new(xx.abs() * sz.w + xy.abs() * sz.h, yx.abs() * sz.w + yy.abs() * sz.h)
     }
 
+    // Rect bounding |r| once transformed.
     #[must_use]
     pub fn rt(&self, r: Rt2D<f64, S>) -> Rt2D<f64, T> {
-        Rt2D::ptsz(self.pt(r.tl()), self.sz(r.sz()))
+        let ps = [r.tl(), r.tr(), r.bl(), r.br()].map(|p| self.pt(p));
+        let x = ps.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let y = ps.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+        Rt2D::ptsz(Pt2D::new(x, y), self.sz(r.sz()))
     }
 
     #[must_use]
@@ -105,9 +177,43 @@ impl<S: Basic, T: Basic> LayerTf<S, T> {
 
     #[must_use]
     pub fn path(&self, p: Path) -> Path {
-        p.transformed(&Translation::new(self.off.x as f32, self.off.y as f32))
+        let [xx, xy, yx, yy] = self.m.map(|v| v as f32);
+        p.transformed(&Transform::new(xx, yx, xy, yy, self.off.x as f32, self.off.y as f32))
     }
 }
 
 pub type PrtTf = LayerTf<LclType, PrtType>;
 pub type GblTf = LayerTf<LclType, GblType>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual::types::{lpt, lrt, lsz, lz};
+
+    fn close<U: Copy>(a: Pt2D<f64, U>, b: Pt2D<f64, U>) -> bool {
+        // Angles are f32, so rotations are only close.
+        (a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5
+    }
+
+    #[test]
+    fn test_affine() {
+        let a = PrtTf::new(lpt(10, 20).coerce(), lz(1).coerce()).rotate(Angle::degrees(90.0));
+        let b: LayerTf<PrtType, GblType> =
+            LayerTf::new(lpt(-3, 4).coerce(), lz(2).coerce()).scale(2.0, 3.0);
+        let ab: GblTf = a.concat(&b);
+        let p = lpt(5, 7);
+        // Rotating (5, 7) clockwise a quarter turn on screen gives (-7, 5).
+        assert!(close(a.pt(p), lpt(3, 25).coerce()));
+        assert!(close(ab.pt(p), b.pt(a.pt(p).coerce())));
+        assert!(close(ab.inv().pt(ab.pt(p)), p));
+        assert_eq!(ab.z(lz(0)), lz(3).coerce());
+        assert_eq!(ab.inv().z(ab.z(lz(5))), lz(5));
+
+        let r = a.rt(lrt(0, 0, 4, 2));
+        assert!(close(r.tl(), lpt(8, 20).coerce()));
+        assert!(close(r.br(), lpt(10, 24).coerce()));
+        assert!((a.avg_scale() - 1.0).abs() < 1e-6);
+        assert!((b.avg_scale() - 6f64.sqrt()).abs() < 1e-9);
+        assert_eq!(PrtTf::default().sz(lsz(3, 4)), lsz(3, 4).coerce());
+    }
+}
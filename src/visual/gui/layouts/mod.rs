@@ -1,4 +1,5 @@
 pub mod hint;
+pub mod horiz_layout;
 pub mod layout;
 pub mod resize_layout;
 mod util;
@@ -26,6 +26,16 @@ enum ResizeDir {
 const Z_OFF: LclZ = ZOrder::new(1000);
 const HOTSPOT_DP: f64 = 18.0;
 const RESIZE_INSET: LclSz = LclSz::new(-16.0, -16.0);
+const SNAP_DP: f64 = 8.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowAction {
+    // Arrange all open windows in a grid covering the screen.
+    Tile,
+    // Toggle between maximized and the previous rect for the window with
+    // the given id.
+    Maximize(String),
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WindowState {
@@ -33,11 +43,27 @@ pub struct WindowState {
     dir: ResizeDir,
     mouse_st: LclPt,
     rt_st: LclRt,
+    #[serde(default)]
+    restore: Option<LclRt>,
+    #[serde(skip)]
+    frame: u64,
 }
 
 impl WindowState {
     fn new(l: LclLayer) -> Self {
-        Self { l, dir: ResizeDir::Move, mouse_st: LclPt::zero(), rt_st: LclRt::zero() }
+        Self {
+            l,
+            dir: ResizeDir::Move,
+            mouse_st: LclPt::zero(),
+            rt_st: LclRt::zero(),
+            restore: None,
+            frame: 0,
+        }
+    }
+
+    // Whether the window was placed in this or the previous frame.
+    fn is_open(&self, frame: u64) -> bool {
+        self.frame != 0 && self.frame + 1 >= frame
     }
 }
 
@@ -45,11 +71,56 @@ impl WindowState {
 pub struct ResizeState {
     wins: HashMap<String, WindowState>,
     top_z: LclZ,
+    #[serde(skip)]
+    actions: Vec<WindowAction>,
 }
 
 impl Default for ResizeState {
     fn default() -> Self {
-        Self { wins: HashMap::default(), top_z: lz(0) }
+        Self { wins: HashMap::default(), top_z: lz(0), actions: Vec::new() }
+    }
+}
+
+impl ResizeState {
+    // Queues an action to be applied at the start of the next layout pass.
+    pub fn request(&mut self, a: WindowAction) {
+        self.actions.push(a);
+    }
+
+    fn next_z(&mut self) -> LclZ {
+        self.top_z += Z_OFF;
+        self.top_z
+    }
+
+    fn front(&self, frame: u64) -> Option<String> {
+        let open = self.wins.iter().filter(|(_, w)| w.is_open(frame));
+        open.max_by_key(|(_, w)| w.l.z).map(|(id, _)| id.clone())
+    }
+
+    fn tile(&mut self, frame: u64, bounds: LclRt) {
+        let mut open: Vec<_> = self.wins.iter_mut().filter(|(_, w)| w.is_open(frame)).collect();
+        // Keep the existing spatial order so windows move as little as possible.
+        open.sort_by(|(a_id, a), (b_id, b)| {
+            a.l.r.y.total_cmp(&b.l.r.y).then(a.l.r.x.total_cmp(&b.l.r.x)).then(a_id.cmp(b_id))
+        });
+        let rts = tile_rts(bounds, open.len());
+        for ((_, w), r) in open.into_iter().zip(rts) {
+            w.l.r = r;
+            w.restore = None;
+        }
+    }
+
+    fn toggle_maximize(&mut self, id: &str, bounds: LclRt) {
+        let z = self.next_z();
+        if let Some(w) = self.wins.get_mut(id) {
+            if let Some(r) = w.restore.take() {
+                w.l.r = r;
+            } else {
+                w.restore = Some(w.l.r);
+                w.l.r = bounds;
+            }
+            w.l.z = z;
+        }
     }
 }
 
@@ -63,12 +134,17 @@ struct ResizeInfo {
 pub struct ResizeLayout {
     info: LayoutInfo,
     loc: LclPt,
+    applied_actions: bool,
 }
 
 impl ResizeLayout {
     #[must_use]
     pub fn new(info: LayoutInfo) -> Self {
-        Self { info, loc: LclPt::zero() }
+        Self { info, loc: LclPt::zero(), applied_actions: false }
+    }
+
+    fn bounds(&self) -> LclRt {
+        LclRt::from_sz(self.info.hint.req.unwrap_or_else(LclSz::zero))
     }
 
     #[allow(clippy::unused_self)]
@@ -83,8 +159,44 @@ impl ResizeLayout {
     }
 
     fn next_z(&self, ui: &mut Ui<'_>) -> LclZ {
-        self.state(ui).top_z += Z_OFF;
-        self.state(ui).top_z
+        self.state(ui).next_z()
+    }
+
+    fn apply_actions(&self, ui: &mut Ui<'_>) {
+        let frame = ui.io().frame_num;
+        let mut actions = std::mem::take(&mut self.state(ui).actions);
+        if ui.io().mods.control_key() {
+            if ui.io().char_pressed("t") {
+                actions.push(WindowAction::Tile);
+            }
+            if ui.io().char_pressed("m")
+                && let Some(id) = self.state(ui).front(frame)
+            {
+                actions.push(WindowAction::Maximize(id));
+            }
+        }
+
+        let bounds = self.bounds();
+        let st = self.state(ui);
+        for a in actions {
+            match a {
+                WindowAction::Tile => st.tile(frame, bounds),
+                WindowAction::Maximize(id) => st.toggle_maximize(&id, bounds),
+            }
+        }
+    }
+
+    fn snap_targets(&self, ui: &mut Ui<'_>, child_id: &str) -> Vec<LclRt> {
+        let frame = ui.io().frame_num;
+        let mut targets = vec![self.bounds()];
+        targets.extend(
+            self.state(ui)
+                .wins
+                .iter()
+                .filter(|(id, w)| id.as_str() != child_id && w.is_open(frame))
+                .map(|(_, w)| w.l.r),
+        );
+        targets
     }
 
     fn handle_click(&self, ui: &mut Ui<'_>, w: &mut WindowState) {
@@ -105,7 +217,8 @@ impl ResizeLayout {
             }
             let mouse_dt = (ltf.pt(ui.io().mouse_pt) - w.mouse_st).to_sz();
             let ResizeInfo { delta_rt, cursor } = get_resize_info(mouse_dt, w.dir, true);
-            w.l.r = w.rt_st + delta_rt;
+            let targets = self.snap_targets(ui, child_id);
+            w.l.r = snap_rt(w.rt_st + delta_rt, w.dir, &targets, SNAP_DP);
             ui.paint_mut().set_cursor(cursor);
         } else if ui.hovered(child_id, hitbox) {
             let ResizeInfo { cursor, .. } = get_resize_info(
@@ -125,6 +238,11 @@ impl LayoutStrategy for ResizeLayout {
     }
 
     fn child_info(&mut self, ui: &mut Ui<'_>, hint: &Hint, child_id: &str) -> LayoutInfo {
+        if !self.applied_actions {
+            self.applied_actions = true;
+            self.apply_actions(ui);
+        }
+
         let mut w = self.state(ui).wins.get(child_id).copied();
 
         if let Some(ref mut w) = w {
@@ -144,8 +262,11 @@ impl LayoutStrategy for ResizeLayout {
 
     fn place_layer(&mut self, ui: &mut Ui<'_>, l: &LclLayer, child_id: &str) {
         // Update saved layer.
-        self.state(ui).wins.entry(child_id.to_owned()).or_insert_with(|| WindowState::new(*l)).l =
-            *l;
+        let frame = ui.io().frame_num;
+        let w =
+            self.state(ui).wins.entry(child_id.to_owned()).or_insert_with(|| WindowState::new(*l));
+        w.l = *l;
+        w.frame = frame;
         self.loc.y = l.r.b();
     }
 }
@@ -181,6 +302,58 @@ fn get_resize_info(d: LclSz, resize_type: ResizeDir, is_captured: bool) -> Resiz
     }
 }
 
+fn tile_rts(bounds: LclRt, n: usize) -> Vec<LclRt> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let cols = (n as f64).sqrt().ceil() as usize;
+    let rows = n.div_ceil(cols);
+    let (w, h) = (bounds.w / cols as f64, bounds.h / rows as f64);
+    (0..n)
+        .map(|i| lrt(bounds.x + (i % cols) as f64 * w, bounds.y + (i / cols) as f64 * h, w, h))
+        .collect()
+}
+
+// Returns the offset to move one of |edges| onto the nearest of |targets|, if
+// any is within |thresh|.
+fn snap_delta(edges: &[f64], targets: &[f64], thresh: f64) -> f64 {
+    edges
+        .iter()
+        .flat_map(|e| targets.iter().map(move |t| t - e))
+        .filter(|d| d.abs() < thresh)
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0)
+}
+
+fn snap_rt(mut r: LclRt, dir: ResizeDir, targets: &[LclRt], thresh: f64) -> LclRt {
+    let xs: Vec<f64> = targets.iter().flat_map(|t| [t.x, t.r()]).collect();
+    let ys: Vec<f64> = targets.iter().flat_map(|t| [t.y, t.b()]).collect();
+    if dir == ResizeDir::Move {
+        r.x += snap_delta(&[r.x, r.r()], &xs, thresh);
+        r.y += snap_delta(&[r.y, r.b()], &ys, thresh);
+        return r;
+    }
+
+    use ResizeDir::{Bottom, BottomLeft, BottomRight, Left, Right, Top, TopLeft, TopRight};
+    if matches!(dir, TopLeft | BottomLeft | Left) {
+        let d = snap_delta(&[r.x], &xs, thresh);
+        r.x += d;
+        r.w -= d;
+    }
+    if matches!(dir, TopRight | BottomRight | Right) {
+        r.w += snap_delta(&[r.r()], &xs, thresh);
+    }
+    if matches!(dir, TopLeft | TopRight | Top) {
+        let d = snap_delta(&[r.y], &ys, thresh);
+        r.y += d;
+        r.h -= d;
+    }
+    if matches!(dir, BottomLeft | BottomRight | Bottom) {
+        r.h += snap_delta(&[r.b()], &ys, thresh);
+    }
+    r
+}
+
 fn resize_dir(l: &LclLayer, mouse: LclPt) -> ResizeDir {
     #[derive(Debug)]
     struct HotspotMatch {
@@ -207,3 +380,49 @@ fn resize_dir(l: &LclLayer, mouse: LclPt) -> ResizeDir {
         _ => ResizeDir::Move,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_rts() {
+        assert_eq!(tile_rts(lrt(0, 0, 100, 100), 0), vec![]);
+        assert_eq!(tile_rts(lrt(0, 0, 100, 100), 1), vec![lrt(0, 0, 100, 100)]);
+        assert_eq!(
+            tile_rts(lrt(0, 0, 100, 100), 3),
+            vec![lrt(0, 0, 50, 50), lrt(50, 0, 50, 50), lrt(0, 50, 50, 50)]
+        );
+        assert_eq!(tile_rts(lrt(10, 0, 90, 30), 3).len(), 3);
+    }
+
+    #[test]
+    fn test_snap_rt() {
+        let scr = [lrt(0, 0, 1000, 1000)];
+        // Moving snaps the nearest edge in each axis and keeps the size.
+        assert_eq!(
+            snap_rt(lrt(5, 993, 100, 5), ResizeDir::Move, &scr, SNAP_DP),
+            lrt(0, 995, 100, 5)
+        );
+        // Nothing nearby leaves the rect alone.
+        assert_eq!(
+            snap_rt(lrt(50, 50, 100, 100), ResizeDir::Move, &scr, SNAP_DP),
+            lrt(50, 50, 100, 100)
+        );
+
+        // Resizing only snaps the edges being dragged.
+        let other = [lrt(200, 0, 100, 100)];
+        assert_eq!(
+            snap_rt(lrt(5, 5, 190, 90), ResizeDir::Right, &other, SNAP_DP),
+            lrt(5, 5, 195, 90)
+        );
+        assert_eq!(
+            snap_rt(lrt(5, 5, 190, 90), ResizeDir::Left, &other, SNAP_DP),
+            lrt(5, 5, 190, 90)
+        );
+        assert_eq!(
+            snap_rt(lrt(3, 5, 190, 90), ResizeDir::TopLeft, &scr, SNAP_DP),
+            lrt(0, 0, 193, 95)
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use std::cell::Cell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use rgb::{ComponentMap, RGBA};
 
 use crate::visual::gui::layer::GblTf;
 use crate::visual::render::painter::PaintCtx;
-use crate::visual::types::{Col, LclSz, LclZ, lsz};
+use crate::visual::types::{Col, LclRt, LclSz, LclZ, lsz};
 use crate::visual::vis::FontId;
 
 pub struct PaintCtxScope {
@@ -43,6 +44,13 @@ impl PaintCtxScope {
         self.pctx.set(self.pctx.get().line_width(line_width));
         self
     }
+
+    #[allow(clippy::must_use_candidate)]
+    pub fn clip(&self, r: LclRt) -> &Self {
+        let pctx = self.pctx.get();
+        self.pctx.set(pctx.clip(pctx.tf.rt(r)));
+        self
+    }
 }
 
 impl Drop for PaintCtxScope {
@@ -51,7 +59,7 @@ impl Drop for PaintCtxScope {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub struct Style {
     pub pad: LclSz,
     pub font: FontId,
@@ -61,6 +69,9 @@ pub struct Style {
     pub acc1_col: Col,
     pub acc2_col: Col,
     pub acc3_col: Col,
+    pub tooltip_delay: Duration,
+    // Colours for data series, e.g. in plots.
+    pub palette: [Col; 8],
 }
 
 impl Style {
@@ -76,8 +87,25 @@ impl Style {
             acc1_col: RGBA::new(249, 220, 92, 255).map(f),
             acc2_col: RGBA::new(250, 130, 76, 255).map(f),
             acc3_col: RGBA::new(60, 145, 230, 255).map(f),
+            tooltip_delay: Duration::from_millis(500),
+            palette: [
+                RGBA::new(60, 145, 230, 255).map(f),
+                RGBA::new(250, 130, 76, 255).map(f),
+                RGBA::new(98, 200, 120, 255).map(f),
+                RGBA::new(249, 220, 92, 255).map(f),
+                RGBA::new(170, 120, 230, 255).map(f),
+                RGBA::new(230, 80, 90, 255).map(f),
+                RGBA::new(80, 200, 210, 255).map(f),
+                RGBA::new(240, 130, 190, 255).map(f),
+            ],
         }
     }
+
+    // Colour of the |i|th data series.
+    #[must_use]
+    pub fn series_col(&self, i: usize) -> Col {
+        self.palette[i % self.palette.len()]
+    }
 }
 
 impl Default for Style {
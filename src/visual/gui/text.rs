@@ -4,6 +4,7 @@ use crate::visual::gui::layer::LclLayer;
 use crate::visual::gui::layouts::hint::Grav;
 use crate::visual::gui::ui::Ui;
 use crate::visual::types::{LclPt, LclRt, lpt, lz};
+use crate::visual::vis::FontId;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Frag {
@@ -11,12 +12,14 @@ pub struct Frag {
     pub grav: (Grav, Grav),
     pub sz: f64,
     pub p: LclPt,
+    // Font to use instead of the style's.
+    pub font: Option<FontId>,
 }
 
 impl Frag {
     #[must_use]
     pub fn new(text: &str, sz: f64, p: LclPt) -> Self {
-        Self { text: text.to_owned(), grav: (Grav::Begin, Grav::Begin), sz, p }
+        Self { text: text.to_owned(), grav: (Grav::Begin, Grav::Begin), sz, p, font: None }
     }
 
     #[must_use]
@@ -24,6 +27,11 @@ impl Frag {
         Self { p, ..self }
     }
 
+    #[must_use]
+    pub fn font(self, font: FontId) -> Self {
+        Self { font: Some(font), ..self }
+    }
+
     #[must_use]
     pub fn hgrav(mut self, g: Grav) -> Self {
         self.grav.0 = g;
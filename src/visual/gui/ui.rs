@@ -1,26 +1,59 @@
 use std::cell::Cell;
+use std::fmt::Display;
 use std::rc::Rc;
 
 use eyre::Result;
 use lyon::math::Angle;
 use lyon::path::Path;
 use num_traits::Zero;
+use rgb::RGBA8;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
 use crate::visual::colors::{GREEN, RED};
-use crate::visual::gui::layer::{LclLayer, PrtLayer};
+use crate::visual::gui::layer::{LclLayer, PrtLayer, PrtTf};
 use crate::visual::gui::layouts::hint::Hint;
 use crate::visual::gui::layouts::layout::{Layout, LayoutInfo};
+use crate::visual::gui::layouts::resize_layout::WindowAction;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
 use crate::visual::gui::style::{PaintCtxScope, Style};
 use crate::visual::gui::text::Frag;
 use crate::visual::gui::widgets::button::Button;
+use crate::visual::gui::widgets::canvas::{Canvas, CanvasUi};
+use crate::visual::gui::widgets::chart::{BarChart, BarSeries, Histogram, PieChart};
+use crate::visual::gui::widgets::collapse::CollapsingHeader;
+use crate::visual::gui::widgets::color_picker::{ColorButton, ColorPicker};
+use crate::visual::gui::widgets::combo::{ComboBox, combo_value};
+use crate::visual::gui::widgets::heatmap::Heatmap;
+use crate::visual::gui::widgets::hex_view::HexView;
+use crate::visual::gui::widgets::image::Image;
+use crate::visual::gui::widgets::image_viewer::ImageViewer;
+use crate::visual::gui::widgets::inspector::Inspector;
+use crate::visual::gui::widgets::json_view::JsonView;
 use crate::visual::gui::widgets::label::Label;
+use crate::visual::gui::widgets::list::{List, RowHeight};
+use crate::visual::gui::widgets::log_console::LogConsole;
+use crate::visual::gui::widgets::menu::{Menu, MenuBar, MenuItem, Separator, SubMenu};
+use crate::visual::gui::widgets::node_graph::{Node, NodeGraph, Pin, Wire};
+use crate::visual::gui::widgets::plot::{Plot, Series};
+use crate::visual::gui::widgets::popup::{Modal, Popup};
+use crate::visual::gui::widgets::progress::{Busy, ProgressBar, Spinner};
+use crate::visual::gui::widgets::stream::{StreamPlot, StreamSeries};
+use crate::visual::gui::widgets::table::{Column, Table};
+use crate::visual::gui::widgets::tabs::Tabs;
+use crate::visual::gui::widgets::tooltip::Tooltip;
+use crate::visual::gui::widgets::tree::{TreeNode, TreeView};
 use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
 use crate::visual::gui::widgets::window::Window;
 use crate::visual::io::Io;
-use crate::visual::render::painter::{PaintCtx, Painter};
-use crate::visual::render::texture::TextureLayer;
-use crate::visual::types::{LclPt, LclRt, LclSz, MAX_Z, Pt};
-use crate::visual::vis::{Memory, Vis};
+use crate::visual::render::image::RgbaImage;
+use crate::visual::render::painter::{Mesh, PaintCtx, Painter};
+use crate::visual::render::texture::{TexId, TextureLayer};
+use crate::visual::types::{
+    Col, GblRt, GblZ, LclPt, LclRt, LclSz, LclZ, MAX_Z, Pt, TexPt, TexRt, TexSz,
+};
+use crate::visual::vis::{Memory, ROOT_ID, Vis};
 
 pub struct Ui<'a> {
     pub s: Style,
@@ -32,7 +65,10 @@ pub struct Ui<'a> {
 
 impl<'a> Ui<'a> {
     pub fn new(v: &'a mut Vis, l: Layout, id: &str) -> Self {
-        let s = Style::new();
+        Self::with_style(v, l, id, Style::new())
+    }
+
+    pub fn with_style(v: &'a mut Vis, l: Layout, id: &str, s: Style) -> Self {
         let pctx = PaintCtx { tf: l.info().gtf, col: s.light_col, ..Default::default() };
         Self { s, v, l, id: id.to_owned(), pctx: Rc::new(Cell::new(pctx)) }
     }
@@ -95,7 +131,10 @@ impl Ui<'_> {
         // accessing via Ui.
         let mut layout = self.l.clone();
         let layer = layout.child(self, hint, child_id, |ui, params| {
-            let mut ui = Ui::new(ui.v, layout_f(params), child_id);
+            // Children stay within their parent's clip rect.
+            let clip = ui.pctx().clip;
+            let mut ui = Ui::with_style(ui.v, layout_f(params), child_id, ui.s);
+            ui.pctx.set(PaintCtx { clip, ..ui.pctx() });
             ui_f(&mut ui)?;
             Ok(ui.l)
         })?;
@@ -108,6 +147,69 @@ impl Ui<'_> {
         Ok(layer)
     }
 
+    // Lays out a child at |p| with global z-order |z| outside of this Ui's
+    // layout, e.g. for popups drawn over everything else.
+    pub fn overlay<UiF>(
+        &mut self,
+        p: LclPt,
+        z: GblZ,
+        hint: &Hint,
+        child_id: &str,
+        mut ui_f: UiF,
+    ) -> Result<LclLayer>
+    where
+        UiF: FnMut(&mut Ui<'_>) -> Result<()>,
+    {
+        let gtf = self.info().gtf;
+        let ptf = PrtTf::new(p.coerce(), (z - gtf.z(LclZ::zero())).coerce());
+        let info = LayoutInfo { gtf: ptf.concat(&gtf.coerce()), ptf, hint: *hint };
+        let mut ui = Ui::with_style(self.v, Layout::new(VertLayout::new(info)), child_id, self.s);
+        ui_f(&mut ui)?;
+        let layer: LclLayer = ui.l.compute_layer().coerce();
+        if self.mem().debug {
+            let scope = self.push();
+            scope.z(MAX_Z).col(GREEN);
+            self.stroke_rt(layer.r);
+        }
+        Ok(layer)
+    }
+
+    // Lays out a child in |l| without placing it in this Ui's layout, e.g. for
+    // the visible rows of a scrolled list. Stays within this Ui's clip rect.
+    pub fn place<UiF>(&mut self, l: LclLayer, child_id: &str, ui_f: UiF) -> Result<LclLayer>
+    where
+        UiF: FnMut(&mut Ui<'_>) -> Result<()>,
+    {
+        self.place_tf(PrtTf::new(l.r.tl().coerce(), l.z.coerce()), l.r.sz(), child_id, ui_f)
+    }
+
+    // Like |place|, but the child of size |sz| is mapped into this Ui by
+    // |ptf|, e.g. to scale or rotate it. Returns the rect bounding the child.
+    pub fn place_tf<UiF>(
+        &mut self,
+        ptf: PrtTf,
+        sz: LclSz,
+        child_id: &str,
+        mut ui_f: UiF,
+    ) -> Result<LclLayer>
+    where
+        UiF: FnMut(&mut Ui<'_>) -> Result<()>,
+    {
+        let gtf = self.info().gtf;
+        let info = LayoutInfo { gtf: ptf.concat(&gtf.coerce()), ptf, hint: Hint::make_exact(sz) };
+        let clip = self.pctx().clip;
+        let mut ui = Ui::with_style(self.v, Layout::new(VertLayout::new(info)), child_id, self.s);
+        ui.pctx.set(PaintCtx { clip, ..ui.pctx() });
+        ui_f(&mut ui)?;
+        let layer: LclLayer = ui.l.compute_layer().coerce();
+        if self.mem().debug {
+            let scope = self.push();
+            scope.z(MAX_Z).col(GREEN);
+            self.stroke_rt(layer.r);
+        }
+        Ok(layer)
+    }
+
     pub fn child_layer(&mut self, hint: &Hint) -> LclLayer {
         // Copy - layouts see a frozen version of themselves from
         // accessing via Ui.
@@ -140,12 +242,67 @@ impl Ui<'_> {
         combine_ids(&[self.id(), &w.lcl_id(self)])
     }
 
+    // Requests a tiling or maximize action on the top level windows. Applied
+    // at the start of the next frame.
+    pub fn window_action(&mut self, a: WindowAction) {
+        self.mem_mut().wid(ROOT_ID).pos.request(a);
+    }
+
+    // Toggles maximizing the top level window with the given title.
+    pub fn maximize_window(&mut self, title: &str) {
+        self.window_action(WindowAction::Maximize(combine_ids(&[ROOT_ID, title])));
+    }
+
+    pub fn copy_text(&mut self, text: &str) -> Result<()> {
+        self.v.copy_text(text)
+    }
+
+    // Gives keyboard focus to |id| until another widget takes it.
+    pub fn focus(&mut self, id: &str) {
+        self.io_mut().kbd_captured = Some(id.to_owned());
+    }
+
+    // Takes keyboard focus from |id|, if it has it.
+    pub fn unfocus(&mut self, id: &str) {
+        if self.focused(id) {
+            self.io_mut().kbd_captured = None;
+        }
+    }
+
+    #[must_use]
+    pub fn focused(&self, id: &str) -> bool {
+        self.io().kbd_captured.as_deref() == Some(id)
+    }
+
+    // Whether any of |l| is on screen and inside the clip rect.
+    #[must_use]
+    pub fn visible(&self, l: LclLayer) -> bool {
+        let r = self.l.info().gtf.layer(l).r;
+        let r = r.intersect(&GblRt::from_sz(self.io().scr_sz));
+        let r = self.pctx().clip.map_or(r, |c| r.intersect(&c));
+        r.w > 0.0 && r.h > 0.0
+    }
+
+    // Keeps frames coming while the animation in |l| can be seen.
+    pub fn animate(&mut self, l: LclLayer) {
+        if self.visible(l) {
+            self.io_mut().request_redraw();
+        }
+    }
+
+    // Whether the mouse is over |l| and inside the clip rect. The mouse is
+    // mapped into local space, so this holds under rotation too.
+    fn contains_mouse(&self, l: LclLayer) -> bool {
+        let mouse = self.io().mouse_pt;
+        l.contains(self.l.info().gtf.inv().pt(mouse)) && self.pctx().contains(mouse)
+    }
+
     pub fn hovered(&mut self, id: &str, l: LclLayer) -> bool {
-        let l = self.l.info().gtf.layer(l);
+        let contained = self.contains_mouse(l);
+        let z = self.l.info().gtf.z(l.z);
         let io = self.v.io_mut();
-        let contained = l.contains(io.mouse_pt);
         if contained {
-            io.mouse_req(l.z, id);
+            io.mouse_req(z, id);
         }
         io.has_mouse.as_deref() == Some(id) && contained
     }
@@ -155,24 +312,30 @@ impl Ui<'_> {
     }
 
     pub fn pressed(&mut self, id: &str, l: LclLayer) -> bool {
-        let l = self.l.info().gtf.layer(l);
+        let contained = self.contains_mouse(l);
+        let z = self.l.info().gtf.z(l.z);
         let io = self.v.io_mut();
-        let capture = io.is_mouse_pressed
-            && (io.mouse_captured.as_deref() == Some(id) || l.contains(io.mouse_pt));
+        let capture =
+            io.is_mouse_pressed && (io.mouse_captured.as_deref() == Some(id) || contained);
         if capture {
-            io.mouse_capture(l.z, id); // Prolong mouse capture.
+            io.mouse_capture(z, id); // Prolong mouse capture.
         }
         io.has_mouse.as_deref() == Some(id) && capture
     }
 
     #[must_use]
     pub fn clicked(&self, id: &str, l: LclLayer) -> bool {
-        let l = self.l.info().gtf.layer(l);
         let io = self.v.io();
         io.has_mouse.as_deref() == Some(id)
             && io.mouse_just_released
             && io.mouse_captured.as_deref() == Some(id)
-            && l.contains(io.mouse_pt)
+            && self.contains_mouse(l)
+    }
+
+    #[must_use]
+    pub fn rclicked(&self, id: &str, l: LclLayer) -> bool {
+        let io = self.v.io();
+        io.has_mouse.as_deref() == Some(id) && io.rmouse_just_released && self.contains_mouse(l)
     }
 }
 
@@ -193,20 +356,240 @@ impl Ui<'_> {
     ) -> Result<Resp> {
         Window::new(title, f).ui(self)
     }
+
+    pub fn popup(
+        &mut self,
+        id: &str,
+        p: LclPt,
+        open: &mut bool,
+        f: impl FnMut(&mut Ui<'_>) -> Result<()>,
+    ) -> Result<Resp> {
+        Popup::new(id, p, open, f).ui(self)
+    }
+
+    pub fn modal(
+        &mut self,
+        id: &str,
+        open: &mut bool,
+        f: impl FnMut(&mut Ui<'_>) -> Result<()>,
+    ) -> Result<Resp> {
+        Modal::new(id, open, f).ui(self)
+    }
+
+    pub fn menu_bar(&mut self, f: impl FnMut(&mut Ui<'_>) -> Result<()>) -> Result<Resp> {
+        MenuBar::new(f).ui(self)
+    }
+
+    pub fn menu(&mut self, text: &str, f: impl FnMut(&mut Ui<'_>) -> Result<()>) -> Result<Resp> {
+        Menu::new(text, f).ui(self)
+    }
+
+    pub fn menu_item<F: FnOnce(&mut Ui<'_>)>(&mut self, text: &str, cb: F) -> Result<Resp> {
+        MenuItem::new(text, cb).ui(self)
+    }
+
+    pub fn menu_check(&mut self, text: &str, checked: &mut bool) -> Result<Resp> {
+        MenuItem::new(text, |_: &mut Ui<'_>| {}).checked(checked).ui(self)
+    }
+
+    pub fn submenu(
+        &mut self,
+        text: &str,
+        f: impl FnMut(&mut Ui<'_>) -> Result<()>,
+    ) -> Result<Resp> {
+        SubMenu::new(text, f).ui(self)
+    }
+
+    pub fn separator(&mut self) -> Result<Resp> {
+        Separator.ui(self)
+    }
+
+    pub fn tabs(
+        &mut self,
+        id: &str,
+        names: &mut Vec<String>,
+        f: impl FnMut(&mut Ui<'_>, &str) -> Result<()>,
+    ) -> Result<Resp> {
+        Tabs::new(id, names, f).ui(self)
+    }
+
+    pub fn combo(&mut self, id: &str, sel: &mut usize, options: Vec<String>) -> Result<Resp> {
+        ComboBox::new(id, sel, options).ui(self)
+    }
+
+    // Combo box choosing |value| from |options| by their `Display` output.
+    pub fn combo_value<T: Display + PartialEq + Clone>(
+        &mut self,
+        id: &str,
+        value: &mut T,
+        options: &[T],
+    ) -> Result<Resp> {
+        combo_value(self, id, value, options)
+    }
+
+    // Pannable and zoomable canvas. |f| draws the content in world space.
+    pub fn canvas(
+        &mut self,
+        id: &str,
+        f: impl FnMut(&mut CanvasUi<'_, '_>) -> Result<()>,
+    ) -> Result<Resp> {
+        Canvas::new(id, f).ui(self)
+    }
+
+    pub fn collapsing(
+        &mut self,
+        text: &str,
+        f: impl FnMut(&mut Ui<'_>) -> Result<()>,
+    ) -> Result<Resp> {
+        CollapsingHeader::new(text, f).ui(self)
+    }
+
+    pub fn tree(
+        &mut self,
+        id: &str,
+        roots: Vec<TreeNode>,
+        sel: &mut Vec<String>,
+        children: impl FnMut(&str) -> Vec<TreeNode>,
+    ) -> Result<Resp> {
+        TreeView::new(id, roots, sel, children).ui(self)
+    }
+
+    // Lists |count| rows, only laying out those in view.
+    pub fn list(
+        &mut self,
+        id: &str,
+        count: usize,
+        row_h: RowHeight<'_>,
+        f: impl FnMut(&mut Ui<'_>, usize) -> Result<()>,
+    ) -> Result<Resp> {
+        List::new(id, count, row_h, f).ui(self)
+    }
+
+    // Node graph editor. |compatible| says whether a wire can go from an
+    // output pin to an input pin.
+    pub fn node_graph(
+        &mut self,
+        id: &str,
+        nodes: &mut Vec<Node>,
+        wires: &mut Vec<Wire>,
+        sel: &mut Vec<String>,
+        compatible: impl FnMut(&Pin, &Pin) -> bool,
+    ) -> Result<Resp> {
+        NodeGraph::new(id, nodes, wires, sel, compatible).ui(self)
+    }
+
+    pub fn plot(&mut self, id: &str, series: Vec<Series<'_>>) -> Result<Resp> {
+        series.into_iter().fold(Plot::new(id), Plot::series).ui(self)
+    }
+
+    pub fn stream_plot(&mut self, id: &str, series: &[StreamSeries]) -> Result<Resp> {
+        StreamPlot::new(id, series).ui(self)
+    }
+
+    pub fn bar_chart(
+        &mut self,
+        id: &str,
+        cats: Vec<String>,
+        series: Vec<BarSeries<'_>>,
+    ) -> Result<Resp> {
+        series.into_iter().fold(BarChart::new(id, cats), BarChart::series).ui(self)
+    }
+
+    pub fn histogram(&mut self, id: &str, samples: &[f64], bins: usize) -> Result<Resp> {
+        Histogram::new(id, samples).bins(bins).ui(self)
+    }
+
+    pub fn pie_chart(&mut self, id: &str, slices: Vec<(String, f64)>) -> Result<Resp> {
+        PieChart::new(id, slices).ui(self)
+    }
+
+    // Edits |value| field by field through its serde representation.
+    pub fn inspector<T: Serialize + DeserializeOwned>(
+        &mut self,
+        id: &str,
+        value: &mut T,
+    ) -> Result<Resp> {
+        Inspector::new(id, value).ui(self)
+    }
+
+    pub fn json_view(&mut self, id: &str, v: &Value) -> Result<Resp> {
+        JsonView::new(id, v).ui(self)
+    }
+
+    pub fn log_console(&mut self, id: &str) -> Result<Resp> {
+        LogConsole::new(id).ui(self)
+    }
+
+    pub fn progress_bar(&mut self, frac: f64) -> Result<Resp> {
+        ProgressBar::new(frac).ui(self)
+    }
+
+    pub fn spinner(&mut self) -> Result<Resp> {
+        Spinner::new().ui(self)
+    }
+
+    pub fn busy(&mut self, text: &str) -> Result<Resp> {
+        Busy::new(text).ui(self)
+    }
+
+    pub fn color_picker(&mut self, id: &str, col: &mut Col) -> Result<Resp> {
+        ColorPicker::new(id, col).ui(self)
+    }
+
+    pub fn color_button(&mut self, id: &str, col: &mut Col) -> Result<Resp> {
+        ColorButton::new(id, col).ui(self)
+    }
+
+    pub fn image(&mut self, tex: TexId) -> Result<Resp> {
+        Image::new(tex).ui(self)
+    }
+
+    pub fn image_viewer(&mut self, id: &str, tex: TexId) -> Result<Resp> {
+        ImageViewer::new(id, tex).ui(self)
+    }
+
+    pub fn hex_view(&mut self, id: &str, data: &[u8]) -> Result<Resp> {
+        HexView::new(id, data).ui(self)
+    }
+
+    pub fn heatmap(&mut self, id: &str, data: &[f64], cols: usize) -> Result<Resp> {
+        Heatmap::new(id, data, cols).ui(self)
+    }
+
+    pub fn table(
+        &mut self,
+        id: &str,
+        cols: Vec<Column>,
+        rows: usize,
+        cell: impl FnMut(usize, usize) -> String,
+    ) -> Result<Resp> {
+        Table::new(id, cols, rows, cell).ui(self)
+    }
+
+    // Shows |text| in a tooltip next to the mouse this frame.
+    pub fn tooltip(&mut self, text: &str) -> Result<Resp> {
+        Tooltip::new(text).ui(self)
+    }
 }
 
 // Drawing.
 #[allow(dead_code)]
 impl Ui<'_> {
+    // Text is laid out at its size on screen, so it stays sharp when scaled.
+    // It isn't rotated or skewed.
     pub fn text_sz(&mut self, f: &Frag) -> Result<LclSz> {
-        let sz = self.v.layout_text(&f.text, f.sz)?;
-        Ok(self.l.info().gtf.inv().sz(sz))
+        let gtf = self.l.info().gtf;
+        let sz =
+            self.v.layout_text(f.font.unwrap_or(self.s.font), &f.text, f.sz * gtf.avg_scale())?;
+        Ok(gtf.inv().sz(sz))
     }
 
     pub fn text(&mut self, f: &Frag) -> Result<()> {
         let l = f.layout(self)?;
-        let l = self.l.info().gtf.layer(l);
-        self.v.draw_text(&f.text, f.sz, &l)
+        let gtf = self.l.info().gtf;
+        let l = gtf.layer(l);
+        let clip = self.pctx().clip;
+        self.v.draw_text(f.font.unwrap_or(self.s.font), &f.text, f.sz * gtf.avg_scale(), &l, clip)
     }
 
     pub fn fill_path(&mut self, p: Path) {
@@ -269,7 +652,82 @@ impl Ui<'_> {
         self.v.paint_mut().stroke_tri(self.pctx.get(), v);
     }
 
+    pub fn fill_mesh(&mut self, m: Mesh) {
+        self.v.paint_mut().fill_mesh(self.pctx.get(), m);
+    }
+
     pub fn tex(&mut self, tex: TextureLayer) {
         self.v.paint_mut().tex(self.pctx.get(), tex);
     }
+
+    pub fn create_tex(&mut self, sz: TexSz) -> TexId {
+        self.v.paint_mut().create_tex(sz)
+    }
+
+    pub fn write_tex(&mut self, id: TexId, data: &[RGBA8]) -> Result<()> {
+        self.v.paint_mut().write_tex(id, data)
+    }
+
+    pub fn write_tex_rect(&mut self, id: TexId, r: TexRt, data: &[RGBA8]) -> Result<()> {
+        self.v.paint_mut().write_tex_rect(id, r, data)
+    }
+
+    pub fn tex_sz(&mut self, id: TexId) -> Result<TexSz> {
+        self.v.paint_mut().tex_sz(id)
+    }
+
+    pub fn create_tex_from(&mut self, img: &RgbaImage) -> Result<TexId> {
+        self.v.paint_mut().create_tex_from(img)
+    }
+
+    pub fn read_tex(&mut self, id: TexId) -> Result<RgbaImage> {
+        self.v.paint_mut().read_tex(id)
+    }
+
+    pub fn tex_px(&mut self, id: TexId, p: TexPt) -> Result<Option<RGBA8>> {
+        self.v.paint_mut().tex_px(id, p)
+    }
+
+    pub fn tex_generation(&mut self, id: TexId) -> Result<u64> {
+        self.v.paint_mut().tex_generation(id)
+    }
+
+    pub fn free_tex(&mut self, id: TexId) {
+        self.v.paint_mut().free_tex(id);
+    }
+
+    // Texture of |sz| owned by widget |id|, refilled with the pixels from |f|
+    // whenever |key| changes, e.g. a hash of what's drawn into it. It's freed
+    // once a frame goes by without this being called for |id|.
+    pub fn widget_tex(
+        &mut self,
+        id: &str,
+        sz: TexSz,
+        key: u64,
+        f: impl FnOnce(&mut Self) -> Result<Vec<RGBA8>>,
+    ) -> Result<TexId> {
+        let old = self.v.wid_tex(id);
+        let tex = match old {
+            Some((tex, old_sz, old_key)) if old_sz == sz => {
+                if old_key == key {
+                    return Ok(tex);
+                }
+                tex
+            }
+            _ => {
+                self.free_widget_tex(id);
+                self.create_tex(sz)
+            }
+        };
+        let data = f(self)?;
+        self.write_tex(tex, &data)?;
+        self.v.set_wid_tex(id, (tex, sz, key));
+        Ok(tex)
+    }
+
+    pub fn free_widget_tex(&mut self, id: &str) {
+        if let Some((tex, _, _)) = self.v.take_wid_tex(id) {
+            self.free_tex(tex);
+        }
+    }
 }
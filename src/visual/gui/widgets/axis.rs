@@ -0,0 +1,392 @@
+use std::any::Any;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::Grav;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::types::{Col, LclPt, LclRt, LclSz, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const LEFT_DP: f64 = 52.0;
+const BOTTOM_DP: f64 = 22.0;
+const PAD_DP: f64 = 8.0;
+const TICK_DP: f64 = 4.0;
+const X_TICK_SPACING_DP: f64 = 80.0;
+const Y_TICK_SPACING_DP: f64 = 40.0;
+const SWATCH_DP: f64 = 10.0;
+const ZOOM_STEP: f64 = 1.1;
+
+// Span of data values along one axis.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Range {
+    #[must_use]
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    // Smallest range covering the finite values in |vs|, if any.
+    pub fn of(vs: impl IntoIterator<Item = f64>) -> Option<Self> {
+        vs.into_iter().filter(|v| v.is_finite()).fold(None, |r, v| match r {
+            None => Some(Self::new(v, v)),
+            Some(r) => Some(Self::new(r.min.min(v), r.max.max(v))),
+        })
+    }
+
+    #[must_use]
+    pub fn span(&self) -> f64 {
+        self.max - self.min
+    }
+
+    #[must_use]
+    pub fn union(&self, o: &Range) -> Self {
+        Self::new(self.min.min(o.min), self.max.max(o.max))
+    }
+
+    // Widens by |frac| of the length on each side. Empty ranges are widened
+    // to a length of one so they can still be shown.
+    #[must_use]
+    pub fn pad(&self, frac: f64) -> Self {
+        if self.span() > 0.0 {
+            let d = self.span() * frac;
+            Self::new(self.min - d, self.max + d)
+        } else {
+            Self::new(self.min - 0.5, self.max + 0.5)
+        }
+    }
+
+    // Maps |v| in this range linearly onto |lo| to |hi|.
+    #[must_use]
+    pub fn map(&self, v: f64, lo: f64, hi: f64) -> f64 {
+        lo + (v - self.min) / self.span() * (hi - lo)
+    }
+
+    #[must_use]
+    pub fn unmap(&self, p: f64, lo: f64, hi: f64) -> f64 {
+        self.min + (p - lo) / (hi - lo) * self.span()
+    }
+
+    // Scales the range about |v|, keeping |v| in place.
+    #[must_use]
+    pub fn zoom(&self, v: f64, factor: f64) -> Self {
+        Self::new(v + (self.min - v) * factor, v + (self.max - v) * factor)
+    }
+
+    #[must_use]
+    pub fn shift(&self, d: f64) -> Self {
+        Self::new(self.min + d, self.max + d)
+    }
+}
+
+// Region of data space shown by a plot.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotView {
+    pub x: Range,
+    pub y: Range,
+}
+
+// View of a plot kept between frames, if it has been set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ViewState {
+    pub view: Option<PlotView>,
+}
+
+#[typetag::serde]
+impl UserData for ViewState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Maps between data space and the plot area on screen. Y points up.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlotArea {
+    pub l: LclLayer,
+    pub view: PlotView,
+}
+
+impl PlotArea {
+    // Plot area inside |l|, leaving room for the tick labels.
+    #[must_use]
+    pub fn new(l: LclLayer, view: PlotView) -> Self {
+        let r = lrt(
+            l.r.x + LEFT_DP,
+            l.r.y + PAD_DP,
+            (l.r.w - LEFT_DP - PAD_DP).max(1.0),
+            (l.r.h - BOTTOM_DP - PAD_DP).max(1.0),
+        );
+        Self { l: LclLayer::new(r, l.z), view }
+    }
+
+    #[must_use]
+    pub fn to_scr(&self, x: f64, y: f64) -> LclPt {
+        let r = self.l.r;
+        lpt(self.view.x.map(x, r.x, r.r()), self.view.y.map(y, r.b(), r.y))
+    }
+
+    // Screen rect covering |x| by |y| in data space.
+    #[must_use]
+    pub fn rt_to_scr(&self, x: Range, y: Range) -> LclRt {
+        let (p0, p1) = (self.to_scr(x.min, y.min), self.to_scr(x.max, y.max));
+        lrt(p0.x.min(p1.x), p0.y.min(p1.y), (p1.x - p0.x).abs(), (p1.y - p0.y).abs())
+    }
+
+    #[must_use]
+    pub fn from_scr(&self, p: LclPt) -> [f64; 2] {
+        let r = self.l.r;
+        [self.view.x.unmap(p.x, r.x, r.r()), self.view.y.unmap(p.y, r.b(), r.y)]
+    }
+}
+
+// Rounds |x| to 1, 2 or 5 times a power of ten. Rounds to the nearest if
+// |round|, otherwise up.
+fn nice_num(x: f64, round: bool) -> f64 {
+    let exp = x.log10().floor();
+    let f = x / 10f64.powf(exp);
+    let nf = if round {
+        match f {
+            f if f < 1.5 => 1.0,
+            f if f < 3.0 => 2.0,
+            f if f < 7.0 => 5.0,
+            _ => 10.0,
+        }
+    } else {
+        match f {
+            f if f <= 1.0 => 1.0,
+            f if f <= 2.0 => 2.0,
+            f if f <= 5.0 => 5.0,
+            _ => 10.0,
+        }
+    };
+    nf * 10f64.powf(exp)
+}
+
+// Nicely spaced ticks inside |r|, roughly |max_ticks| of them. Returns the
+// ticks and the spacing between them.
+#[must_use]
+pub fn ticks(r: Range, max_ticks: usize) -> (Vec<f64>, f64) {
+    if !r.min.is_finite() || !r.max.is_finite() || r.span() <= 0.0 {
+        return (Vec::new(), 0.0);
+    }
+    let step = nice_num(nice_num(r.span(), false) / max_ticks.max(2) as f64, true);
+    let first = (r.min / step).ceil();
+    let n = ((r.max / step).floor() - first).max(-1.0) as usize;
+    let ticks = (0..=n).map(|i| (first + i as f64) * step).filter(|&t| t <= r.max).collect();
+    (ticks, step)
+}
+
+// Formats |v| with just enough decimals to tell apart ticks |step| apart.
+#[must_use]
+pub fn fmt_tick(v: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    // Avoid showing "-0".
+    let v = if v.abs() < step * 1e-6 { 0.0 } else { v };
+    format!("{v:.decimals$}")
+}
+
+// How an axis is labelled.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Ticks<'a> {
+    // Nicely spaced numbers, with grid lines.
+    Values,
+    // Named categories, the |i|th centred on i + 0.5.
+    Categories(&'a [String]),
+    // Nicely spaced numbers counting down from the given top, e.g. rows
+    // numbered from the top.
+    Descending(f64),
+}
+
+impl Ticks<'_> {
+    // Positions and labels of the ticks inside |r|, about |max_ticks| of them.
+    fn labels(&self, r: Range, max_ticks: usize) -> Vec<(f64, String)> {
+        match self {
+            Self::Values => {
+                let (ts, step) = ticks(r, max_ticks);
+                ts.into_iter().map(|t| (t, fmt_tick(t, step))).collect()
+            }
+            Self::Descending(top) => {
+                let (ts, step) = ticks(r, max_ticks);
+                ts.into_iter().map(|t| (t, fmt_tick(top - t, step))).collect()
+            }
+            Self::Categories(cats) => cats
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i as f64 + 0.5, c.clone()))
+                .filter(|(v, _)| *v >= r.min && *v <= r.max)
+                .collect(),
+        }
+    }
+}
+
+// Draws tick labels along the bottom and left of |a| labelled by |x| and |y|,
+// grid lines for numeric axes, and a frame.
+pub fn draw_axes(ui: &mut Ui<'_>, a: &PlotArea, x: Ticks<'_>, y: Ticks<'_>) -> Result<()> {
+    let r = a.l.r;
+    let (nx, ny) = ((r.w / X_TICK_SPACING_DP) as usize, (r.h / Y_TICK_SPACING_DP) as usize);
+
+    let scope = ui.push();
+    scope.z(a.l.z);
+    for (v, label) in x.labels(a.view.x, nx) {
+        let p = a.to_scr(v, 0.0);
+        if x == Ticks::Values {
+            scope.col(ui.s.light_col.with_alpha(0.1));
+            ui.stroke_line(lpt(p.x, r.y), lpt(p.x, r.b()));
+        }
+        scope.col(ui.s.light_col.with_alpha(0.6));
+        ui.stroke_line(lpt(p.x, r.b()), lpt(p.x, r.b() + TICK_DP));
+        let f = Frag::new(&label, ui.s.font_sz, lpt(p.x, r.b() + TICK_DP));
+        ui.text(&f.hgrav(Grav::Center))?;
+    }
+    for (v, label) in y.labels(a.view.y, ny) {
+        let p = a.to_scr(0.0, v);
+        if y == Ticks::Values {
+            scope.col(ui.s.light_col.with_alpha(0.1));
+            ui.stroke_line(lpt(r.x, p.y), lpt(r.r(), p.y));
+        }
+        scope.col(ui.s.light_col.with_alpha(0.6));
+        ui.stroke_line(lpt(r.x - TICK_DP, p.y), lpt(r.x, p.y));
+        let f = Frag::new(&label, ui.s.font_sz, lpt(r.x - 2.0 * TICK_DP, p.y));
+        ui.text(&f.hgrav(Grav::End).vgrav(Grav::Center))?;
+    }
+    scope.col(ui.s.light_col.with_alpha(0.3));
+    ui.stroke_rt(r);
+    Ok(())
+}
+
+// Draws a legend of |entries| in the top right of |l|, above the data.
+pub fn draw_legend(ui: &mut Ui<'_>, l: LclLayer, entries: &[(&str, Col)]) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut w: f64 = 0.0;
+    let mut row_h: f64 = SWATCH_DP;
+    let mut frags = Vec::with_capacity(entries.len());
+    for (name, _) in entries {
+        let f = Frag::new(name, ui.s.font_sz, LclPt::zero());
+        let sz = ui.text_sz(&f)?;
+        w = w.max(sz.w);
+        row_h = row_h.max(sz.h);
+        frags.push(f);
+    }
+    let pad = TICK_DP;
+    let w = w + SWATCH_DP + 3.0 * pad;
+    let h = entries.len() as f64 * row_h + 2.0 * pad;
+    let bg = lrt(l.r.r() - w - PAD_DP, l.r.y + PAD_DP, w, h);
+
+    let scope = ui.push();
+    scope.z(l.z + lz(2)).col(ui.s.dark_col.with_alpha(0.8));
+    ui.fill_rrt(bg, 4.0);
+    for (i, ((_, col), f)) in entries.iter().zip(frags).enumerate() {
+        let cy = bg.y + pad + (i as f64 + 0.5) * row_h;
+        scope.col(*col);
+        ui.fill_rt(LclRt::ptsz(lpt(bg.x + pad, cy - SWATCH_DP / 2.0), lsz(SWATCH_DP, SWATCH_DP)));
+        scope.col(ui.s.light_col);
+        ui.text(&f.pt(lpt(bg.x + SWATCH_DP + 2.0 * pad, cy)).vgrav(Grav::Center))?;
+    }
+    Ok(())
+}
+
+// Wheel and drag input for panning and zooming a view, in local space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PanZoom {
+    // Whether the view is hovered or being dragged.
+    pub active: bool,
+    pub mouse: LclPt,
+    // Wheel steps while hovered, and zero otherwise.
+    pub wheel: f64,
+    // How far the view was dragged this frame.
+    pub drag: LclSz,
+}
+
+// Reads the input for panning and zooming the view in |l| with id |id|. Only
+// scrolls while |l| is hovered, so it doesn't zoom under popups.
+pub fn pan_zoom_input(ui: &mut Ui<'_>, id: &str, l: LclLayer) -> PanZoom {
+    let hovered = ui.hovered(id, l);
+    let pressed = ui.pressed(id, l);
+    let io = ui.io();
+    let gtf = ui.info().gtf;
+    let mouse = gtf.inv().pt(io.mouse_pt);
+    let prev = gtf.inv().pt(io.mouse_pt - io.mouse_delta.to_sz());
+    let wheel = if hovered { io.mouse_scroll.y } else { 0.0 };
+    let drag =
+        if pressed && !io.mouse_just_captured { (mouse - prev).to_sz() } else { LclSz::zero() };
+    PanZoom { active: hovered || pressed, mouse, wheel, drag }
+}
+
+// Zooms with the wheel about the cursor and pans by dragging. Shift zooms
+// only along x and ctrl only along y. Returns whether the plot is hovered.
+pub fn pan_zoom(ui: &mut Ui<'_>, id: &str, a: &mut PlotArea) -> bool {
+    let pz = pan_zoom_input(ui, id, a.l);
+    let mods = ui.io().mods;
+    if !pz.wheel.is_zero() {
+        let [x, y] = a.from_scr(pz.mouse);
+        let factor = ZOOM_STEP.powf(-pz.wheel);
+        if !mods.control_key() {
+            a.view.x = a.view.x.zoom(x, factor);
+        }
+        if !mods.shift_key() {
+            a.view.y = a.view.y.zoom(y, factor);
+        }
+    }
+    let r = a.l.r;
+    a.view.x = a.view.x.shift(-pz.drag.w / r.w * a.view.x.span());
+    a.view.y = a.view.y.shift(pz.drag.h / r.h * a.view.y.span());
+    pz.active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_nice_num() {
+        assert_eq!(nice_num(0.13, true), 0.1);
+        assert_eq!(nice_num(2.6, true), 2.0);
+        assert_eq!(nice_num(3.2, false), 5.0);
+        assert_eq!(nice_num(73.0, false), 100.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_ticks() {
+        let (ts, step) = ticks(Range::new(0.0, 10.0), 5);
+        assert_eq!((ts.len(), step), (6, 2.0));
+        assert_eq!(ts, [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+
+        let (ts, step) = ticks(Range::new(-0.33, 0.72), 10);
+        assert_eq!(step, 0.2);
+        assert_eq!(
+            ts.iter().map(|&t| fmt_tick(t, step)).collect::<Vec<_>>(),
+            ["-0.2", "0.0", "0.2", "0.4", "0.6"]
+        );
+
+        assert!(ticks(Range::new(1.0, 1.0), 5).0.is_empty());
+        assert!(ticks(Range::new(0.0, f64::NAN), 5).0.is_empty());
+    }
+
+    #[test]
+    fn test_fmt_tick() {
+        assert_eq!(fmt_tick(1500.0, 500.0), "1500");
+        assert_eq!(fmt_tick(0.25, 0.05), "0.25");
+        assert_eq!(fmt_tick(-1e-17, 0.1), "0.0");
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(Range::of([3.0, f64::NAN, -1.0, 2.0]), Some(Range::new(-1.0, 3.0)));
+        assert_eq!(Range::of([]), None);
+        assert_eq!(Range::new(0.0, 10.0).zoom(5.0, 0.5), Range::new(2.5, 7.5));
+        assert_eq!(Range::new(2.0, 2.0).pad(0.1), Range::new(1.5, 2.5));
+        let r = Range::new(-1.0, 1.0);
+        assert!((r.unmap(r.map(0.3, 100.0, 0.0), 100.0, 0.0) - 0.3).abs() < 1e-9);
+    }
+}
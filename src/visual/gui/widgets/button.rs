@@ -1,11 +1,17 @@
 use eyre::Result;
+use num_traits::Zero;
 
-use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
 use crate::visual::gui::layouts::layout::Layout;
 use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
 use crate::visual::gui::ui::Ui;
 use crate::visual::gui::widgets::label::Label;
 use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::LclPt;
+
+const TOOL_PAD_DP: f64 = 6.0;
 
 #[derive(Debug)]
 pub struct Button<F: FnOnce(&mut Ui<'_>)> {
@@ -48,10 +54,37 @@ impl<F: FnOnce(&mut Ui<'_>)> Widget for Button<F> {
         scope.col(col);
         ui.fill_rrt(l.r, 4.0);
 
-        Ok(Resp { l })
+        Ok(Resp { id, l })
     }
 
     fn lcl_id(&self, ui: &Ui<'_>) -> String {
         combine_ids(&["button", &self.label.lcl_id(ui)])
     }
 }
+
+// Width of a `tool_button` showing |text|.
+pub fn tool_button_w(ui: &mut Ui<'_>, text: &str) -> Result<f64> {
+    Ok(ui.text_sz(&Frag::new(text, ui.s.font_sz, LclPt::zero()))?.w + 2.0 * TOOL_PAD_DP)
+}
+
+// Button drawn in |l| rather than laid out, e.g. in a toolbar. Highlighted
+// while |on|. Returns whether it was clicked.
+pub fn tool_button(ui: &mut Ui<'_>, id: &str, l: LclLayer, text: &str, on: bool) -> Result<bool> {
+    let hovered = ui.hovered(id, l);
+    ui.pressed(id, l);
+    let clicked = ui.clicked(id, l);
+    let scope = ui.push();
+    let alpha = if on {
+        0.5
+    } else if hovered {
+        0.3
+    } else {
+        0.1
+    };
+    scope.z(l.z).col(ui.s.light_col.with_alpha(alpha));
+    ui.fill_rrt(l.r, 3.0);
+    scope.col(ui.s.light_col);
+    let f = Frag::new(text, ui.s.font_sz, l.r.center());
+    ui.text(&f.hgrav(Grav::Center).vgrav(Grav::Center))?;
+    Ok(clicked)
+}
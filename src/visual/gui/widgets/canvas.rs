@@ -0,0 +1,402 @@
+use eyre::Result;
+use lyon::math::{Transform, vector};
+use lyon::path::Path;
+use num_traits::Zero;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{
+    PlotView, Range, ViewState, fmt_tick, pan_zoom_input, ticks,
+};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{
+    LclPt, LclRt, LclSz, LclZ, WldPt, WldRt, WldSz, lpt, lrt, lsz, lz, wpt, wsz,
+};
+
+const ZOOM_STEP: f64 = 1.1;
+const GRID_DP: f64 = 24.0;
+const LABEL_DP: f64 = 80.0;
+const TOP_RULER_DP: f64 = 18.0;
+const LEFT_RULER_DP: f64 = 44.0;
+const TICK_DP: f64 = 4.0;
+// Text smaller than this on screen is left out, since it'd be unreadable.
+const MIN_TEXT_DP: f64 = 3.0;
+
+// Maps between world space and local space. |o| is the world point at the top
+// left of |r|, and |s| is the zoom in dp per world unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CanvasView {
+    pub r: LclRt,
+    pub o: WldPt,
+    pub s: f64,
+}
+
+impl CanvasView {
+    // View onto |r| of the remembered |view|, or of the world from the origin
+    // at a zoom of one.
+    #[must_use]
+    pub fn new(r: LclRt, view: Option<PlotView>) -> Self {
+        match view {
+            Some(v) if v.x.span() > 0.0 => {
+                Self { r, o: wpt(v.x.min, v.y.min), s: r.w / v.x.span() }
+            }
+            _ => Self { r, o: WldPt::zero(), s: 1.0 },
+        }
+    }
+
+    // Region of the world in view, to be remembered.
+    #[must_use]
+    pub fn view(&self) -> PlotView {
+        let w = self.wld_rt(self.r);
+        PlotView { x: Range::new(w.x, w.r()), y: Range::new(w.y, w.b()) }
+    }
+
+    #[must_use]
+    pub fn lcl(&self, p: WldPt) -> LclPt {
+        lpt(self.r.x + (p.x - self.o.x) * self.s, self.r.y + (p.y - self.o.y) * self.s)
+    }
+
+    #[must_use]
+    pub fn lcl_sz(&self, sz: WldSz) -> LclSz {
+        lsz(sz.w * self.s, sz.h * self.s)
+    }
+
+    #[must_use]
+    pub fn lcl_rt(&self, r: WldRt) -> LclRt {
+        LclRt::ptsz(self.lcl(r.tl()), self.lcl_sz(r.sz()))
+    }
+
+    #[must_use]
+    pub fn wld(&self, p: LclPt) -> WldPt {
+        wpt(self.o.x + (p.x - self.r.x) / self.s, self.o.y + (p.y - self.r.y) / self.s)
+    }
+
+    #[must_use]
+    pub fn wld_sz(&self, sz: LclSz) -> WldSz {
+        wsz(sz.w / self.s, sz.h / self.s)
+    }
+
+    #[must_use]
+    pub fn wld_rt(&self, r: LclRt) -> WldRt {
+        WldRt::ptsz(self.wld(r.tl()), self.wld_sz(r.sz()))
+    }
+
+    // Transform taking paths in world space to local space.
+    #[must_use]
+    pub fn path_tf(&self) -> Transform {
+        let (x, y) = (self.r.x - self.o.x * self.s, self.r.y - self.o.y * self.s);
+        Transform::scale(self.s as f32, self.s as f32).then_translate(vector(x as f32, y as f32))
+    }
+
+    // Moves the view so the world follows a mouse move of |d|.
+    pub fn pan(&mut self, d: LclSz) {
+        self.o -= self.wld_sz(d);
+    }
+
+    // Zooms by |steps| wheel steps, keeping the world point under |p| still.
+    // The zoom is kept within |lim|.
+    pub fn zoom_at(&mut self, p: LclPt, steps: f64, lim: Range) {
+        let w = self.wld(p);
+        self.s = (self.s * ZOOM_STEP.powf(steps)).clamp(lim.min, lim.max);
+        self.o = wpt(w.x - (p.x - self.r.x) / self.s, w.y - (p.y - self.r.y) / self.s);
+    }
+}
+
+// Handed to the drawing function of a canvas. The helpers take world units and
+// draw with the current paint context, so line widths stay in screen dp. Use
+// |dp| for lengths which should scale with the zoom instead. |ui| can be used
+// directly to draw in local space, e.g. labels which keep their size.
+pub struct CanvasUi<'a, 'b> {
+    pub ui: &'a mut Ui<'b>,
+    pub view: CanvasView,
+    // Z of the canvas content. Hit areas are placed here, over the background.
+    pub z: LclZ,
+    mouse: LclPt,
+}
+
+impl CanvasUi<'_, '_> {
+    // World length |w| in dp at the current zoom.
+    #[must_use]
+    pub fn dp(&self, w: f64) -> f64 {
+        w * self.view.s
+    }
+
+    // Region of the world in view, e.g. to skip drawing what's off screen.
+    #[must_use]
+    pub fn visible(&self) -> WldRt {
+        self.view.wld_rt(self.view.r)
+    }
+
+    // World point under the mouse, if it's over the canvas.
+    #[must_use]
+    pub fn mouse(&self) -> Option<WldPt> {
+        self.view.r.contains(self.mouse).then(|| self.view.wld(self.mouse))
+    }
+
+    fn layer(&self, r: WldRt) -> LclLayer {
+        LclLayer::new(self.view.lcl_rt(r), self.z)
+    }
+
+    pub fn hovered(&mut self, id: &str, r: WldRt) -> bool {
+        let l = self.layer(r);
+        self.ui.hovered(id, l)
+    }
+
+    pub fn pressed(&mut self, id: &str, r: WldRt) -> bool {
+        let l = self.layer(r);
+        self.ui.pressed(id, l)
+    }
+
+    #[must_use]
+    pub fn clicked(&self, id: &str, r: WldRt) -> bool {
+        self.ui.clicked(id, self.layer(r))
+    }
+
+    pub fn fill_rt(&mut self, r: WldRt) {
+        self.ui.fill_rt(self.view.lcl_rt(r));
+    }
+
+    pub fn stroke_rt(&mut self, r: WldRt) {
+        self.ui.stroke_rt(self.view.lcl_rt(r));
+    }
+
+    pub fn fill_rrt(&mut self, r: WldRt, radius: f64) {
+        self.ui.fill_rrt(self.view.lcl_rt(r), radius * self.view.s);
+    }
+
+    pub fn stroke_rrt(&mut self, r: WldRt, radius: f64) {
+        self.ui.stroke_rrt(self.view.lcl_rt(r), radius * self.view.s);
+    }
+
+    pub fn fill_circ(&mut self, p: WldPt, radius: f64) {
+        self.ui.fill_circ(self.view.lcl(p), radius * self.view.s);
+    }
+
+    pub fn stroke_circ(&mut self, p: WldPt, radius: f64) {
+        self.ui.stroke_circ(self.view.lcl(p), radius * self.view.s);
+    }
+
+    pub fn stroke_line(&mut self, st: WldPt, en: WldPt) {
+        self.ui.stroke_line(self.view.lcl(st), self.view.lcl(en));
+    }
+
+    pub fn fill_poly(&mut self, pts: &[WldPt]) {
+        self.ui.fill_poly(pts.iter().map(|&p| self.view.lcl(p)).collect());
+    }
+
+    pub fn stroke_poly(&mut self, pts: &[WldPt]) {
+        self.ui.stroke_poly(pts.iter().map(|&p| self.view.lcl(p)).collect());
+    }
+
+    pub fn fill_path(&mut self, p: Path) {
+        self.ui.fill_path(p.transformed(&self.view.path_tf()));
+    }
+
+    pub fn stroke_path(&mut self, p: Path) {
+        self.ui.stroke_path(p.transformed(&self.view.path_tf()));
+    }
+
+    // Draws |f| at |p| with its size in world units, ignoring the point it
+    // has. Text too small to read is skipped.
+    pub fn text(&mut self, f: &Frag, p: WldPt) -> Result<()> {
+        let sz = f.sz * self.view.s;
+        if sz < MIN_TEXT_DP {
+            return Ok(());
+        }
+        let mut f = f.clone().pt(self.view.lcl(p));
+        f.sz = sz;
+        self.ui.text(&f)
+    }
+}
+
+// An unbounded surface for drawing in world space. The wheel zooms about the
+// cursor, dragging the background pans and right clicking it goes back to the
+// origin. |f| draws the content each frame through a |CanvasUi|. The view is
+// remembered per canvas.
+#[derive(Debug)]
+pub struct Canvas<F: FnMut(&mut CanvasUi<'_, '_>) -> Result<()>> {
+    id: String,
+    f: F,
+    grid: bool,
+    zoom: Range,
+    sz: LclSz,
+}
+
+impl<F: FnMut(&mut CanvasUi<'_, '_>) -> Result<()>> Canvas<F> {
+    pub fn new(id: &str, f: F) -> Self {
+        Self { id: id.to_owned(), f, grid: true, zoom: Range::new(1e-3, 1e3), sz: lsz(600, 400) }
+    }
+
+    // Whether to draw a grid behind the content, with rulers along the top
+    // and left.
+    #[must_use]
+    pub fn grid(self, grid: bool) -> Self {
+        Self { grid, ..self }
+    }
+
+    // Smallest and largest zoom, in dp per world unit.
+    #[must_use]
+    pub fn zoom(self, min: f64, max: f64) -> Self {
+        Self { zoom: Range::new(min, max), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    fn draw_grid(ui: &mut Ui<'_>, view: &CanvasView, z: LclZ) {
+        let (r, vis) = (view.r, view.view());
+        let (nx, ny) = ((r.w / GRID_DP) as usize, (r.h / GRID_DP) as usize);
+        let scope = ui.push();
+        scope.z(z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rt(r);
+        scope.col(ui.s.light_col.with_alpha(0.06)).line_width(1.0);
+        for x in ticks(vis.x, nx).0 {
+            let p = view.lcl(wpt(x, 0.0));
+            ui.stroke_line(lpt(p.x, r.y), lpt(p.x, r.b()));
+        }
+        for y in ticks(vis.y, ny).0 {
+            let p = view.lcl(wpt(0.0, y));
+            ui.stroke_line(lpt(r.x, p.y), lpt(r.r(), p.y));
+        }
+        // Axes through the world origin.
+        let o = view.lcl(WldPt::zero());
+        scope.col(ui.s.light_col.with_alpha(0.25));
+        if o.x >= r.x && o.x <= r.r() {
+            ui.stroke_line(lpt(o.x, r.y), lpt(o.x, r.b()));
+        }
+        if o.y >= r.y && o.y <= r.b() {
+            ui.stroke_line(lpt(r.x, o.y), lpt(r.r(), o.y));
+        }
+    }
+
+    // Draws rulers in the strips of |l| above and left of the view, marking
+    // where the mouse is if it's over the view.
+    fn draw_rulers(ui: &mut Ui<'_>, l: LclLayer, view: &CanvasView, mouse: LclPt) -> Result<()> {
+        let (r, vis) = (view.r, view.view());
+        let top = lrt(r.x, l.r.y, r.w, TOP_RULER_DP);
+        let left = lrt(l.r.x, r.y, LEFT_RULER_DP, r.h);
+        let (nx, ny) = ((r.w / LABEL_DP) as usize, (r.h / LABEL_DP) as usize);
+        let font_sz = ui.s.font_sz * 0.8;
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col);
+        ui.fill_rt(lrt(l.r.x, l.r.y, l.r.w, TOP_RULER_DP));
+        ui.fill_rt(left);
+        scope.clip(top);
+        let (xs, step) = ticks(vis.x, nx);
+        for x in xs {
+            let p = view.lcl(wpt(x, 0.0));
+            scope.col(ui.s.light_col.with_alpha(0.6));
+            ui.stroke_line(lpt(p.x, top.b() - TICK_DP), lpt(p.x, top.b()));
+            let f = Frag::new(&fmt_tick(x, step), font_sz, lpt(p.x + 2.0, top.y + 1.0));
+            ui.text(&f)?;
+        }
+        drop(scope);
+        let scope = ui.push();
+        scope.z(l.z).clip(left);
+        let (ys, step) = ticks(vis.y, ny);
+        for y in ys {
+            let p = view.lcl(wpt(0.0, y));
+            scope.col(ui.s.light_col.with_alpha(0.6));
+            ui.stroke_line(lpt(left.r() - TICK_DP, p.y), lpt(left.r(), p.y));
+            let f = Frag::new(&fmt_tick(y, step), font_sz, lpt(left.r() - TICK_DP - 2.0, p.y));
+            ui.text(&f.hgrav(Grav::End).vgrav(Grav::Center))?;
+        }
+        drop(scope);
+
+        if r.contains(mouse) {
+            let scope = ui.push();
+            scope.z(l.z).col(ui.s.acc3_col).line_width(1.0);
+            ui.stroke_line(lpt(mouse.x, top.y), lpt(mouse.x, top.b()));
+            ui.stroke_line(lpt(left.x, mouse.y), lpt(left.r(), mouse.y));
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&mut CanvasUi<'_, '_>) -> Result<()>> Widget for Canvas<F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let r = if self.grid {
+            lrt(
+                l.r.x + LEFT_RULER_DP,
+                l.r.y + TOP_RULER_DP,
+                (l.r.w - LEFT_RULER_DP).max(1.0),
+                (l.r.h - TOP_RULER_DP).max(1.0),
+            )
+        } else {
+            l.r
+        };
+        let bg = LclLayer::new(r, l.z);
+        let mut view = CanvasView::new(r, ui.mem_mut().user::<ViewState>(&id)?.view);
+        let pz = pan_zoom_input(ui, &id, bg);
+        let mouse = pz.mouse;
+        view.pan(pz.drag);
+        if !pz.wheel.is_zero() {
+            view.zoom_at(mouse, pz.wheel, self.zoom);
+        }
+        if ui.rclicked(&id, bg) {
+            view = CanvasView::new(r, None);
+        }
+        ui.mem_mut().user::<ViewState>(&id)?.view = Some(view.view());
+
+        if self.grid {
+            Self::draw_grid(ui, &view, l.z);
+        }
+        {
+            let scope = ui.push();
+            scope.z(l.z + lz(1)).clip(r).col(ui.s.light_col);
+            (self.f)(&mut CanvasUi { ui, view, z: l.z + lz(1), mouse })?;
+        }
+        if self.grid {
+            // Over the content, so nothing drawn past the edge of the view
+            // hides them.
+            Self::draw_rulers(ui, LclLayer::new(l.r, l.z + lz(2)), &view, mouse)?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["canvas", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: WldPt, b: WldPt) -> bool {
+        (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_view() {
+        let lim = Range::new(0.2, 4.0);
+        let mut v = CanvasView::new(lrt(10, 20, 200, 100), None);
+        let p = lpt(50, 60);
+        assert!(close(v.wld(v.lcl(wpt(3, -4))), wpt(3, -4)));
+        let w = v.wld(p);
+        v.zoom_at(p, 3.0, lim);
+        assert!(close(v.wld(p), w));
+        let back = CanvasView::new(v.r, Some(v.view()));
+        assert!(close(back.o, v.o));
+        assert!((back.s - v.s).abs() < 1e-9);
+        v.pan(lsz(20, -10));
+        assert!(close(v.wld(p + lsz(20, -10)), w));
+        v.zoom_at(p, 100.0, lim);
+        assert!((v.s - lim.max).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_path_tf() {
+        let v = CanvasView { r: lrt(10, 20, 200, 100), o: wpt(-5, 2), s: 2.5 };
+        let p = v.path_tf().transform_point(lyon::math::point(3.0, 4.0));
+        let q = v.lcl(wpt(3, 4));
+        assert!((f64::from(p.x) - q.x).abs() < 1e-4 && (f64::from(p.y) - q.y).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,489 @@
+use std::f64::consts::TAU;
+
+use eyre::Result;
+
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{
+    PlotArea, PlotView, Range, Ticks, draw_axes, draw_legend, fmt_tick, ticks,
+};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{Col, LclPt, LclRt, LclSz, lpt, lsz, lz};
+
+// Fraction of each category taken up by its bars.
+const BAR_FILL: f64 = 0.8;
+const ARC_STEP: f64 = 0.05;
+const PIE_PAD_DP: f64 = 28.0;
+const POP_DP: f64 = 6.0;
+const LABEL_DP: f64 = 8.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BarMode {
+    Grouped,
+    Stacked,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarSeries<'a> {
+    pub name: String,
+    pub vals: &'a [f64],
+    pub col: Option<Col>,
+}
+
+impl<'a> BarSeries<'a> {
+    #[must_use]
+    pub fn new(name: &str, vals: &'a [f64]) -> Self {
+        Self { name: name.to_owned(), vals, col: None }
+    }
+
+    #[must_use]
+    pub fn col(self, col: Col) -> Self {
+        Self { col: Some(col), ..self }
+    }
+}
+
+// A bar in data space, spanning |cat| along the category axis and |val| along
+// the value axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Bar {
+    series: usize,
+    idx: usize,
+    cat: Range,
+    val: Range,
+}
+
+fn layout_bars(series: &[BarSeries<'_>], n: usize, mode: BarMode) -> Vec<Bar> {
+    let w = BAR_FILL / series.len().max(1) as f64;
+    // Stacks grow up from zero for positive values and down for negative.
+    let (mut pos, mut neg) = (vec![0.0; n], vec![0.0; n]);
+    let mut bars = Vec::new();
+    for (si, s) in series.iter().enumerate() {
+        for (i, &v) in s.vals.iter().enumerate().take(n) {
+            if !v.is_finite() {
+                continue;
+            }
+            let c0 = i as f64 + (1.0 - BAR_FILL) / 2.0;
+            let (cat, val) = match mode {
+                BarMode::Grouped => {
+                    let lo = c0 + si as f64 * w;
+                    (Range::new(lo, lo + w), Range::new(0.0, v))
+                }
+                BarMode::Stacked => {
+                    let base = if v >= 0.0 { &mut pos[i] } else { &mut neg[i] };
+                    let v0 = *base;
+                    *base += v;
+                    (Range::new(c0, c0 + BAR_FILL), Range::new(v0, *base))
+                }
+            };
+            bars.push(Bar { series: si, idx: i, cat, val });
+        }
+    }
+    bars
+}
+
+// Range of values to show for |bars|, always including zero with some room
+// beyond the longest bar.
+fn value_range(bars: &[Bar]) -> Range {
+    let r = Range::of(bars.iter().flat_map(|b| [b.val.min, b.val.max]).chain([0.0]))
+        .filter(|r| r.span() > 0.0)
+        .unwrap_or(Range::new(0.0, 1.0));
+    let d = r.span() * 0.05;
+    Range::new(
+        if r.min < 0.0 { r.min - d } else { r.min },
+        if r.max > 0.0 { r.max + d } else { r.max },
+    )
+}
+
+// Draws |bars| with each bar's data rect given by |rt|, highlighting and
+// describing the hovered bar. Returns the hovered bar, if any.
+fn draw_bars<'b>(
+    ui: &mut Ui<'_>,
+    a: &PlotArea,
+    bars: &'b [Bar],
+    cols: &[Col],
+    rt: impl Fn(&Bar) -> LclRt,
+    hovered: bool,
+) -> Option<&'b Bar> {
+    let mouse = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+    let scope = ui.push();
+    scope.z(a.l.z + lz(1)).clip(a.l.r);
+    let mut hit = None;
+    for b in bars {
+        let r = rt(b);
+        let over = hovered && r.contains(mouse);
+        if over {
+            hit = Some(b);
+        }
+        scope.col(cols[b.series].with_alpha(if over { 1.0 } else { 0.8 }));
+        ui.fill_rt(r);
+    }
+    hit
+}
+
+// Vertical or horizontal bar chart of values in categories |cats|. Several
+// series are shown side by side or stacked.
+#[derive(Debug)]
+pub struct BarChart<'a> {
+    id: String,
+    cats: Vec<String>,
+    series: Vec<BarSeries<'a>>,
+    mode: BarMode,
+    horizontal: bool,
+    sz: LclSz,
+}
+
+impl<'a> BarChart<'a> {
+    #[must_use]
+    pub fn new(id: &str, cats: Vec<String>) -> Self {
+        Self {
+            id: id.to_owned(),
+            cats,
+            series: Vec::new(),
+            mode: BarMode::Grouped,
+            horizontal: false,
+            sz: lsz(400, 250),
+        }
+    }
+
+    #[must_use]
+    pub fn series(mut self, s: BarSeries<'a>) -> Self {
+        self.series.push(s);
+        self
+    }
+
+    #[must_use]
+    pub fn mode(self, mode: BarMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    // Bars grow to the right, with the first category at the top.
+    #[must_use]
+    pub fn horizontal(self, horizontal: bool) -> Self {
+        Self { horizontal, ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+}
+
+impl Widget for BarChart<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let n = self.cats.len();
+        let bars = layout_bars(&self.series, n, self.mode);
+        let vals = value_range(&bars);
+        let cats = Range::new(0.0, n as f64);
+        let cols: Vec<_> = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.col.unwrap_or(ui.s.series_col(i)))
+            .collect();
+
+        let rev: Vec<_> = self.cats.iter().rev().cloned().collect();
+        let (a, x_ticks, y_ticks) = if self.horizontal {
+            let a = PlotArea::new(l, PlotView { x: vals, y: cats });
+            (a, Ticks::Values, Ticks::Categories(&rev))
+        } else {
+            let a = PlotArea::new(l, PlotView { x: cats, y: vals });
+            (a, Ticks::Categories(&self.cats), Ticks::Values)
+        };
+        let hovered = ui.hovered(&id, a.l);
+        draw_axes(ui, &a, x_ticks, y_ticks)?;
+
+        let horizontal = self.horizontal;
+        let rt = |b: &Bar| {
+            if horizontal {
+                a.rt_to_scr(b.val, Range::new(cats.max - b.cat.max, cats.max - b.cat.min))
+            } else {
+                a.rt_to_scr(b.cat, b.val)
+            }
+        };
+        if let Some(b) = draw_bars(ui, &a, &bars, &cols, rt, hovered) {
+            let s = &self.series[b.series];
+            let (_, step) = ticks(vals, 100);
+            let v = fmt_tick(s.vals[b.idx], step);
+            ui.tooltip(&format!("{} / {}: {v}", s.name, self.cats[b.idx]))?;
+        }
+
+        if self.series.len() > 1 {
+            let entries: Vec<_> =
+                self.series.iter().zip(&cols).map(|(s, c)| (s.name.as_str(), *c)).collect();
+            draw_legend(ui, a.l, &entries)?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["bar_chart", &self.id])
+    }
+}
+
+// Counts |samples| into |n| equal bins spanning |r|. Samples outside |r| are
+// ignored, and the last bin includes its upper edge.
+fn bin(samples: &[f64], n: usize, r: Range) -> Vec<usize> {
+    let mut counts = vec![0; n];
+    if n == 0 || r.span() <= 0.0 {
+        return counts;
+    }
+    for &v in samples {
+        if !(r.min..=r.max).contains(&v) {
+            continue;
+        }
+        let i = (((v - r.min) / r.span() * n as f64) as usize).min(n - 1);
+        counts[i] += 1;
+    }
+    counts
+}
+
+// Histogram of raw |samples| counted into equal bins.
+#[derive(Debug)]
+pub struct Histogram<'a> {
+    id: String,
+    samples: &'a [f64],
+    bins: usize,
+    range: Option<Range>,
+    col: Option<Col>,
+    sz: LclSz,
+}
+
+impl<'a> Histogram<'a> {
+    #[must_use]
+    pub fn new(id: &str, samples: &'a [f64]) -> Self {
+        Self { id: id.to_owned(), samples, bins: 20, range: None, col: None, sz: lsz(400, 250) }
+    }
+
+    #[must_use]
+    pub fn bins(self, bins: usize) -> Self {
+        Self { bins: bins.max(1), ..self }
+    }
+
+    // Range of values to bin. Defaults to the range of the samples.
+    #[must_use]
+    pub fn range(self, range: Range) -> Self {
+        Self { range: Some(range), ..self }
+    }
+
+    #[must_use]
+    pub fn col(self, col: Col) -> Self {
+        Self { col: Some(col), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+}
+
+impl Widget for Histogram<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let r = self.range.or_else(|| Range::of(self.samples.iter().copied()));
+        let r = r.unwrap_or(Range::new(0.0, 1.0));
+        let r = if r.span() > 0.0 { r } else { r.pad(0.0) };
+        let counts = bin(self.samples, self.bins, r);
+        let w = r.span() / self.bins as f64;
+        let bars: Vec<_> = counts
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let lo = r.min + i as f64 * w;
+                Bar {
+                    series: 0,
+                    idx: i,
+                    cat: Range::new(lo, lo + w),
+                    val: Range::new(0.0, c as f64),
+                }
+            })
+            .collect();
+
+        let a = PlotArea::new(l, PlotView { x: r, y: value_range(&bars) });
+        let hovered = ui.hovered(&id, a.l);
+        draw_axes(ui, &a, Ticks::Values, Ticks::Values)?;
+        let col = self.col.unwrap_or(ui.s.series_col(0));
+        if let Some(b) = draw_bars(ui, &a, &bars, &[col], |b| a.rt_to_scr(b.cat, b.val), hovered) {
+            let (_, step) = ticks(r, self.bins * 10);
+            let (lo, hi) = (fmt_tick(b.cat.min, step), fmt_tick(b.cat.max, step));
+            // The last bin also counts samples equal to the maximum.
+            let close = if b.idx + 1 == self.bins { ']' } else { ')' };
+            ui.tooltip(&format!("[{lo}, {hi}{close}: {}", counts[b.idx]))?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["histogram", &self.id])
+    }
+}
+
+// Start and end angles of each slice, clockwise from the top. Slices with
+// values which aren't positive are empty.
+fn slice_angles(vals: &[f64]) -> Vec<Range> {
+    let total: f64 = vals.iter().filter(|v| **v > 0.0).sum();
+    let mut a = 0.0;
+    vals.iter()
+        .map(|&v| {
+            let d = if v > 0.0 && total > 0.0 { v / total * TAU } else { 0.0 };
+            a += d;
+            Range::new(a - d, a)
+        })
+        .collect()
+}
+
+// Point at |angle| clockwise from the top, |radius| from |c|.
+fn on_circle(c: LclPt, radius: f64, angle: f64) -> LclPt {
+    lpt(c.x + angle.sin() * radius, c.y - angle.cos() * radius)
+}
+
+// Pie chart of |slices|, or a donut chart if given a hole.
+#[derive(Debug)]
+pub struct PieChart {
+    id: String,
+    slices: Vec<(String, f64)>,
+    hole: f64,
+    legend: bool,
+    sz: LclSz,
+}
+
+impl PieChart {
+    #[must_use]
+    pub fn new(id: &str, slices: Vec<(String, f64)>) -> Self {
+        Self { id: id.to_owned(), slices, hole: 0.0, legend: false, sz: lsz(250, 250) }
+    }
+
+    // Radius of the hole as a fraction of the outer radius.
+    #[must_use]
+    pub fn hole(self, hole: f64) -> Self {
+        Self { hole: hole.clamp(0.0, 0.95), ..self }
+    }
+
+    #[must_use]
+    pub fn legend(self, legend: bool) -> Self {
+        Self { legend, ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    fn slice_poly(c: LclPt, outer: f64, inner: f64, a: Range) -> Vec<LclPt> {
+        let n = (a.span() / ARC_STEP).ceil().max(1.0) as usize;
+        let arc =
+            |r: f64| (0..=n).map(move |i| on_circle(c, r, a.min + a.span() * i as f64 / n as f64));
+        let mut pts: Vec<_> = arc(outer).collect();
+        if inner > 0.0 {
+            pts.extend(arc(inner).collect::<Vec<_>>().into_iter().rev());
+        } else {
+            pts.push(c);
+        }
+        pts
+    }
+}
+
+impl Widget for PieChart {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let c = l.r.center();
+        let outer = (l.r.w.min(l.r.h) / 2.0 - PIE_PAD_DP).max(1.0);
+        let inner = outer * self.hole;
+        let vals: Vec<_> = self.slices.iter().map(|(_, v)| *v).collect();
+        let angles = slice_angles(&vals);
+        let total: f64 = vals.iter().filter(|v| **v > 0.0).sum();
+
+        // Find the slice under the mouse from its angle and distance.
+        let hovered = ui.hovered(&id, l);
+        let d = (ui.info().gtf.inv().pt(ui.io().mouse_pt) - c).to_sz();
+        let dist = d.w.hypot(d.h);
+        let angle = d.w.atan2(-d.h).rem_euclid(TAU);
+        let hit = (hovered && (inner..=outer).contains(&dist))
+            .then(|| angles.iter().position(|a| a.span() > 0.0 && a.min <= angle && angle < a.max))
+            .flatten();
+
+        let scope = ui.push();
+        for (i, ((name, v), a)) in self.slices.iter().zip(&angles).enumerate() {
+            if a.span() <= 0.0 {
+                continue;
+            }
+            // Pop the hovered slice out a little.
+            let mid = f64::midpoint(a.min, a.max);
+            let off = if hit == Some(i) { on_circle(c, POP_DP, mid) } else { c };
+            scope.z(l.z + lz(1)).col(ui.s.series_col(i));
+            ui.fill_poly(Self::slice_poly(off, outer, inner, *a));
+            scope.col(ui.s.dark_col);
+            ui.stroke_poly(Self::slice_poly(off, outer, inner, *a));
+
+            // Label outside the slice, growing away from the pie.
+            let p = on_circle(c, outer + LABEL_DP, mid);
+            let hgrav = if mid < TAU / 2.0 { Grav::Begin } else { Grav::End };
+            scope.col(ui.s.light_col);
+            ui.text(&Frag::new(name, ui.s.font_sz, p).hgrav(hgrav).vgrav(Grav::Center))?;
+
+            if hit == Some(i) {
+                ui.tooltip(&format!("{name}: {v} ({:.1}%)", v / total * 100.0))?;
+            }
+        }
+        drop(scope);
+
+        if self.legend {
+            let entries: Vec<_> = self
+                .slices
+                .iter()
+                .enumerate()
+                .map(|(i, (name, _))| (name.as_str(), ui.s.series_col(i)))
+                .collect();
+            draw_legend(ui, l, &entries)?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["pie_chart", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_layout_bars() {
+        let (a, b) = ([1.0, -2.0], [3.0, f64::NAN]);
+        let series = [BarSeries::new("a", &a), BarSeries::new("b", &b)];
+
+        let bars = layout_bars(&series, 2, BarMode::Grouped);
+        assert_eq!(bars.len(), 3);
+        assert!((bars[0].cat.min - 0.1).abs() < 1e-9 && (bars[0].cat.max - 0.5).abs() < 1e-9);
+        assert!((bars[2].cat.min - 0.5).abs() < 1e-9);
+        assert_eq!(bars[1].val, Range::new(0.0, -2.0));
+
+        let bars = layout_bars(&series, 2, BarMode::Stacked);
+        assert_eq!(bars[2].val, Range::new(1.0, 4.0));
+        assert_eq!(bars[2].cat, bars[0].cat);
+        assert_eq!(value_range(&bars).min, -2.0 - 6.0 * 0.05);
+        assert_eq!(layout_bars(&series, 1, BarMode::Stacked).len(), 2);
+    }
+
+    #[test]
+    fn test_bin() {
+        let samples = [0.0, 0.1, 0.5, 0.99, 1.0, 2.0, f64::NAN];
+        assert_eq!(bin(&samples, 2, Range::new(0.0, 1.0)), [2, 3]);
+        assert_eq!(bin(&samples, 4, Range::new(0.0, 2.0)), [2, 2, 1, 1]);
+        assert_eq!(bin(&samples, 3, Range::new(1.0, 1.0)), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_slice_angles() {
+        let a = slice_angles(&[1.0, 0.0, 3.0, -1.0]);
+        assert_eq!(a[0], Range::new(0.0, TAU / 4.0));
+        assert!(a[1].span().abs() < 1e-12 && a[3].span().abs() < 1e-12);
+        assert!((a[2].max - TAU).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,134 @@
+use std::any::Any;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::layouts::horiz_layout::HorizLayout;
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, lpt, lsz};
+use crate::visual::vis::UserData;
+
+pub const INDENT_DP: f64 = 16.0;
+pub const ROW_PAD_DP: f64 = 4.0;
+
+// Draws a disclosure triangle centered on |c|, pointing down when |open| and
+// right otherwise.
+pub fn disclosure(ui: &mut Ui<'_>, c: LclPt, open: bool) {
+    if open {
+        ui.fill_poly(vec![
+            lpt(c.x - 4.0, c.y - 2.0),
+            lpt(c.x + 4.0, c.y - 2.0),
+            lpt(c.x, c.y + 3.0),
+        ]);
+    } else {
+        ui.stroke_tri([lpt(c.x - 2.0, c.y - 4.0), lpt(c.x + 3.0, c.y), lpt(c.x - 2.0, c.y + 4.0)]);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CollapseState {
+    // Whether it's open, once it has been toggled.
+    open: Option<bool>,
+}
+
+#[typetag::serde]
+impl UserData for CollapseState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Whether the collapsible widget |id| is open. Persisted across runs.
+pub fn is_open(ui: &mut Ui<'_>, id: &str, default_open: bool) -> Result<bool> {
+    Ok(ui.mem_mut().user::<CollapseState>(id)?.open.unwrap_or(default_open))
+}
+
+pub fn set_open(ui: &mut Ui<'_>, id: &str, open: bool) -> Result<()> {
+    ui.mem_mut().user::<CollapseState>(id)?.open = Some(open);
+    Ok(())
+}
+
+// Lays out |f| indented by one level.
+pub fn indented(ui: &mut Ui<'_>, mut f: impl FnMut(&mut Ui<'_>) -> Result<()>) -> Result<LclLayer> {
+    let id = combine_ids(&[ui.id(), "indent"]);
+    let inner_id = combine_ids(&[&id, "inner"]);
+    ui.child(
+        &Hint::new(),
+        &id,
+        |info| Layout::new(HorizLayout::new(info)),
+        |ui| {
+            ui.child_layer(&Hint::make_exact(lsz(INDENT_DP, 0.0)));
+            ui.child(&Hint::new(), &inner_id, |info| Layout::new(VertLayout::new(info)), &mut f)?;
+            Ok(())
+        },
+    )
+}
+
+// A clickable header which shows or hides its contents.
+#[derive(Debug)]
+pub struct CollapsingHeader<F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    text: String,
+    default_open: bool,
+    f: F,
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> CollapsingHeader<F> {
+    pub fn new(text: &str, f: F) -> Self {
+        Self { text: text.to_owned(), default_open: false, f }
+    }
+
+    #[must_use]
+    pub fn default_open(self, default_open: bool) -> Self {
+        Self { default_open, ..self }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for CollapsingHeader<F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child(
+            &Hint::new(),
+            &id,
+            |info| Layout::new(VertLayout::new(info)),
+            |ui| {
+                let f = Frag::new(&self.text, ui.s.font_sz, LclPt::zero());
+                let sz = ui.text_sz(&f)? + lsz(INDENT_DP + ROW_PAD_DP, ROW_PAD_DP);
+                let hl = ui.child_layer(&Hint::make_exact(sz));
+                let header_id = combine_ids(&[&id, "header"]);
+                let hovered = ui.hovered(&header_id, hl);
+                ui.pressed(&header_id, hl);
+                let mut open = is_open(ui, &id, self.default_open)?;
+                if ui.clicked(&header_id, hl) {
+                    open = !open;
+                    set_open(ui, &id, open)?;
+                }
+
+                if hovered {
+                    let scope = ui.push();
+                    scope.col(ui.s.light_col.with_alpha(0.2));
+                    ui.fill_rrt(hl.r, 4.0);
+                }
+                let cy = hl.r.center().y;
+                disclosure(ui, lpt(hl.r.x + INDENT_DP / 2.0, cy), open);
+                ui.text(&f.pt(lpt(hl.r.x + INDENT_DP, cy)).vgrav(Grav::Center))?;
+
+                if open {
+                    indented(ui, &mut self.f)?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["collapsing", &self.text])
+    }
+}
@@ -0,0 +1,431 @@
+use std::any::Any;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::popup::Popup;
+use crate::visual::gui::widgets::text_edit::{edit_buf, edit_text};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::render::painter::Mesh;
+use crate::visual::types::{Col, LclRt, LclZ, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const SQ_DP: f64 = 160.0;
+const STRIP_DP: f64 = 16.0;
+const GAP_DP: f64 = 6.0;
+const ROW_DP: f64 = 22.0;
+const PAD_DP: f64 = 4.0;
+const CHECK_DP: f64 = 6.0;
+
+// RGB of |hsv|, with all components from 0 to 1.
+#[must_use]
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let h = h.rem_euclid(1.0) * 6.0;
+    let f = h - h.floor();
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - s * f), v * (1.0 - s * (1.0 - f)));
+    match h as u32 {
+        0 => [v, t, p],
+        1 => [q, v, p],
+        2 => [p, v, t],
+        3 => [p, q, v],
+        4 => [t, p, v],
+        _ => [v, p, q],
+    }
+}
+
+// HSV of |rgb|. Greys have a hue of zero.
+#[must_use]
+#[allow(clippy::float_cmp)]
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let d = max - r.min(g).min(b);
+    let s = if max > 0.0 { d / max } else { 0.0 };
+    let h = if d <= 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    [h / 6.0, s, max]
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// "#rrggbb", or "#rrggbbaa" if |c| is translucent.
+#[must_use]
+pub fn to_hex(c: Col) -> String {
+    let [r, g, b, a] = [c.r, c.g, c.b, c.a].map(to_u8);
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+// Parses "rgb", "rrggbb" or "rrggbbaa", with or without a leading '#'.
+#[must_use]
+pub fn parse_hex(s: &str) -> Option<Col> {
+    let s = s.trim();
+    let s = s.strip_prefix('#').unwrap_or(s);
+    if !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let v: Vec<u8> = match s.len() {
+        3 => s.chars().filter_map(|c| c.to_digit(16)).map(|d| d as u8 * 17).collect(),
+        6 | 8 => (0..s.len())
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+            .collect(),
+        _ => return None,
+    };
+    let f = |i: usize| v.get(i).map_or(1.0, |&d| f32::from(d) / 255.0);
+    Some(Col::new(f(0), f(1), f(2), f(3)))
+}
+
+fn rgb(c: Col) -> [f32; 3] {
+    [c.r, c.g, c.b]
+}
+
+fn opaque([r, g, b]: [f32; 3]) -> Col {
+    Col::new(r, g, b, 1.0)
+}
+
+// Checkerboard shown behind translucent colours.
+fn checker(ui: &mut Ui<'_>, r: LclRt) {
+    let (a, b) = (Col::new(0.6, 0.6, 0.6, 1.0), Col::new(0.4, 0.4, 0.4, 1.0));
+    let (nx, ny) = ((r.w / CHECK_DP).ceil() as usize, (r.h / CHECK_DP).ceil() as usize);
+    let mut m = Mesh::new();
+    for j in 0..ny {
+        for i in 0..nx {
+            let (x, y) = (r.x + i as f64 * CHECK_DP, r.y + j as f64 * CHECK_DP);
+            let c = if (i + j) % 2 == 0 { a } else { b };
+            m.rt(lrt(x, y, CHECK_DP.min(r.r() - x), CHECK_DP.min(r.b() - y)), [c; 4]);
+        }
+    }
+    ui.fill_mesh(m);
+}
+
+// Handles dragging in |r|. Returns where the mouse is in |r| from 0 to 1 on
+// each axis while it is dragged.
+fn drag(ui: &mut Ui<'_>, id: &str, r: LclRt, z: LclZ) -> Option<[f32; 2]> {
+    let l = LclLayer::new(r, z);
+    ui.hovered(id, l);
+    if !ui.pressed(id, l) {
+        return None;
+    }
+    let p = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+    Some([((p.x - r.x) / r.w).clamp(0.0, 1.0) as f32, ((p.y - r.y) / r.h).clamp(0.0, 1.0) as f32])
+}
+
+// Text field showing |value| after |label|. Clicking it edits the text, which
+// is returned when Enter is pressed or the field loses focus.
+fn field(
+    ui: &mut Ui<'_>,
+    id: &str,
+    l: LclLayer,
+    label: &str,
+    value: &str,
+) -> Result<Option<String>> {
+    // The text being edited is kept while the field has focus.
+    let mut buf = if ui.focused(id) { std::mem::take(edit_buf(ui, id)?) } else { value.to_owned() };
+    let st = edit_text(ui, id, l, &mut buf);
+    let (focused, hovered) = (st.focused, st.hovered);
+    let entered = st.committed.then(|| buf.clone());
+
+    let scope = ui.push();
+    let alpha = if focused {
+        0.3
+    } else if hovered {
+        0.25
+    } else {
+        0.15
+    };
+    scope.z(l.z).col(ui.s.light_col.with_alpha(alpha)).clip(l.r);
+    ui.fill_rrt(l.r, 3.0);
+    let cy = l.r.center().y;
+    scope.col(ui.s.light_col.with_alpha(0.6));
+    ui.text(&Frag::new(label, ui.s.font_sz, lpt(l.r.x + PAD_DP, cy)).vgrav(Grav::Center))?;
+    let text = if focused { format!("{buf}|") } else { value.to_owned() };
+    scope.col(ui.s.light_col);
+    let f = Frag::new(&text, ui.s.font_sz, lpt(l.r.r() - PAD_DP, cy));
+    ui.text(&f.hgrav(Grav::End).vgrav(Grav::Center))?;
+    if focused {
+        *edit_buf(ui, id)? = buf;
+    }
+    Ok(entered)
+}
+
+// Colour being edited as HSV, so the hue is kept while it is grey.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PickerState {
+    #[serde(skip)]
+    hsv: Option<[f32; 3]>,
+}
+
+#[typetag::serde]
+impl UserData for PickerState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Edits a colour with a saturation/value square, hue and alpha strips, and
+// fields for the hex code and each channel from 0 to 255.
+#[derive(Debug)]
+pub struct ColorPicker<'a> {
+    id: String,
+    col: &'a mut Col,
+}
+
+impl<'a> ColorPicker<'a> {
+    pub fn new(id: &str, col: &'a mut Col) -> Self {
+        Self { id: id.to_owned(), col }
+    }
+
+    fn draw_strips(
+        ui: &mut Ui<'_>,
+        z: LclZ,
+        hue: LclRt,
+        alpha: LclRt,
+        [h, s, v]: [f32; 3],
+        a: f32,
+    ) {
+        let scope = ui.push();
+        scope.z(z);
+        let mut m = Mesh::new();
+        for i in 0..6 {
+            let (t0, t1) = (i as f32 / 6.0, (i + 1) as f32 / 6.0);
+            let (c0, c1) = (opaque(hsv_to_rgb([t0, 1.0, 1.0])), opaque(hsv_to_rgb([t1, 1.0, 1.0])));
+            let y0 = hue.y + f64::from(t0) * hue.h;
+            m.rt(lrt(hue.x, y0, hue.w, hue.h / 6.0), [c0, c0, c1, c1]);
+        }
+        ui.fill_mesh(m);
+
+        checker(ui, alpha);
+        let c = opaque(hsv_to_rgb([h, s, v]));
+        let mut m = Mesh::new();
+        m.rt(alpha, [c, c, c.with_alpha(0.0), c.with_alpha(0.0)]);
+        scope.z(z + lz(1));
+        ui.fill_mesh(m);
+
+        scope.z(z + lz(2)).col(ui.s.light_col);
+        for (r, t) in [(hue, h), (alpha, 1.0 - a)] {
+            let y = r.y + f64::from(t) * r.h;
+            ui.stroke_rt(lrt(r.x - 1.0, y - 2.0, r.w + 2.0, 4.0));
+        }
+    }
+}
+
+impl Widget for ColorPicker<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let w = SQ_DP + 2.0 * (GAP_DP + STRIP_DP);
+        let l = ui.child_layer(&Hint::make_exact(lsz(w, SQ_DP + 2.0 * (GAP_DP + ROW_DP))));
+        let sq = lrt(l.r.x, l.r.y, SQ_DP, SQ_DP);
+        let hue = lrt(sq.r() + GAP_DP, l.r.y, STRIP_DP, SQ_DP);
+        let alpha = lrt(hue.r() + GAP_DP, l.r.y, STRIP_DP, SQ_DP);
+        let row1 = lrt(l.r.x, sq.b() + GAP_DP, w, ROW_DP);
+        let row2 = lrt(l.r.x, row1.b() + GAP_DP, w, ROW_DP);
+
+        // Keep the stored HSV unless the colour was changed elsewhere.
+        let c = *self.col;
+        let close =
+            |hsv: [f32; 3]| hsv_to_rgb(hsv).iter().zip(rgb(c)).all(|(a, b)| (a - b).abs() < 1e-3);
+        let mut hsv = ui
+            .mem_mut()
+            .user::<PickerState>(&id)?
+            .hsv
+            .filter(|&hsv| close(hsv))
+            .unwrap_or_else(|| rgb_to_hsv(rgb(c)));
+        let mut a = c.a;
+        let mut changed = false;
+        if let Some([x, y]) = drag(ui, &combine_ids(&[&id, "sv"]), sq, l.z) {
+            (hsv[1], hsv[2], changed) = (x, 1.0 - y, true);
+        }
+        if let Some([_, y]) = drag(ui, &combine_ids(&[&id, "hue"]), hue, l.z) {
+            (hsv[0], changed) = (y, true);
+        }
+        if let Some([_, y]) = drag(ui, &combine_ids(&[&id, "alpha"]), alpha, l.z) {
+            (a, changed) = (1.0 - y, true);
+        }
+        if changed {
+            let [r, g, b] = hsv_to_rgb(hsv);
+            *self.col = Col::new(r, g, b, a);
+        }
+
+        // Text entry. A valid entry replaces the colour, and its HSV.
+        let swatch = lrt(row1.x, row1.y, 2.0 * ROW_DP, ROW_DP);
+        let hex_l = LclLayer::new(
+            lrt(swatch.r() + PAD_DP, row1.y, row1.r() - swatch.r() - PAD_DP, ROW_DP),
+            l.z,
+        );
+        let mut entered = None;
+        if let Some(s) = field(ui, &combine_ids(&[&id, "hex"]), hex_l, "Hex", &to_hex(*self.col))? {
+            entered = parse_hex(&s);
+        }
+        let fw = (w - 3.0 * PAD_DP) / 4.0;
+        for (i, label) in ["R", "G", "B", "A"].into_iter().enumerate() {
+            let fl = LclLayer::new(lrt(row2.x + i as f64 * (fw + PAD_DP), row2.y, fw, ROW_DP), l.z);
+            let mut ch = [self.col.r, self.col.g, self.col.b, self.col.a];
+            let value = to_u8(ch[i]).to_string();
+            if let Some(s) = field(ui, &combine_ids(&[&id, label]), fl, label, &value)?
+                && let Ok(v) = s.trim().parse::<u8>()
+            {
+                ch[i] = f32::from(v) / 255.0;
+                entered = Some(Col::new(ch[0], ch[1], ch[2], ch[3]));
+            }
+        }
+        if let Some(c) = entered {
+            *self.col = c;
+            hsv = rgb_to_hsv(rgb(c));
+            a = c.a;
+        }
+        ui.mem_mut().user::<PickerState>(&id)?.hsv = Some(hsv);
+
+        // Saturation increases to the right and value upwards: white blended
+        // to the hue, then darkened towards the bottom.
+        let [h, s, v] = hsv;
+        let white = Col::new(1.0, 1.0, 1.0, 1.0);
+        let (hc, black) = (opaque(hsv_to_rgb([h, 1.0, 1.0])), Col::new(0.0, 0.0, 0.0, 1.0));
+        let scope = ui.push();
+        scope.z(l.z);
+        let mut m = Mesh::new();
+        m.rt(sq, [white, hc, hc, white]);
+        ui.fill_mesh(m);
+        let mut m = Mesh::new();
+        m.rt(sq, [black.with_alpha(0.0), black.with_alpha(0.0), black, black]);
+        scope.z(l.z + lz(1));
+        ui.fill_mesh(m);
+        let p = lpt(sq.x + f64::from(s) * sq.w, sq.y + f64::from(1.0 - v) * sq.h);
+        scope.z(l.z + lz(2)).col(ui.s.dark_col);
+        ui.stroke_circ(p, 5.0);
+        scope.col(ui.s.light_col);
+        ui.stroke_circ(p, 6.0);
+        drop(scope);
+        Self::draw_strips(ui, l.z, hue, alpha, hsv, a);
+        draw_swatch(ui, swatch, l.z, *self.col);
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["color_picker", &self.id])
+    }
+}
+
+// |c| in |r|, opaque on the left half and with its alpha on the right.
+fn draw_swatch(ui: &mut Ui<'_>, r: LclRt, z: LclZ, c: Col) {
+    let scope = ui.push();
+    scope.z(z);
+    checker(ui, r);
+    scope.z(z + lz(1)).col(c.with_alpha(1.0));
+    ui.fill_rt(lrt(r.x, r.y, r.w / 2.0, r.h));
+    scope.col(c);
+    ui.fill_rt(lrt(r.x + r.w / 2.0, r.y, r.w / 2.0, r.h));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ColorButtonState {
+    #[serde(skip)]
+    open: bool,
+}
+
+#[typetag::serde]
+impl UserData for ColorButtonState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Swatch of a colour which opens a `ColorPicker` in a popup when clicked.
+#[derive(Debug)]
+pub struct ColorButton<'a> {
+    id: String,
+    col: &'a mut Col,
+}
+
+impl<'a> ColorButton<'a> {
+    pub fn new(id: &str, col: &'a mut Col) -> Self {
+        Self { id: id.to_owned(), col }
+    }
+}
+
+impl Widget for ColorButton<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(lsz(2.0 * ROW_DP, ROW_DP)));
+
+        // As for a combo box, decide whether the popup is shown before this
+        // press can dismiss it.
+        let hovered = ui.hovered(&id, l);
+        ui.pressed(&id, l);
+        let was_open = ui.mem_mut().user::<ColorButtonState>(&id)?.open;
+        if hovered && ui.io().mouse_just_pressed {
+            ui.mem_mut().user::<ColorButtonState>(&id)?.open = !was_open;
+        }
+
+        draw_swatch(ui, l.r, l.z, *self.col);
+        let scope = ui.push();
+        let alpha = if hovered || was_open { 0.6 } else { 0.3 };
+        scope.z(l.z + lz(2)).col(ui.s.light_col.with_alpha(alpha));
+        ui.stroke_rt(l.r);
+        drop(scope);
+
+        if was_open {
+            let mut open = true;
+            let popup_id = combine_ids(&[&self.id, "popup"]);
+            Popup::new(&popup_id, l.r.bl(), &mut open, |ui| {
+                ColorPicker::new("picker", self.col).ui(ui)?;
+                Ok(())
+            })
+            .ui(ui)?;
+            ui.mem_mut().user::<ColorButtonState>(&id)?.open &= open;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["color_button", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert!(close(hsv_to_rgb([0.0, 1.0, 1.0]), [1.0, 0.0, 0.0]));
+        assert!(close(hsv_to_rgb([1.0, 1.0, 1.0]), [1.0, 0.0, 0.0]));
+        assert!(close(hsv_to_rgb([0.5, 0.5, 0.5]), [0.25, 0.5, 0.5]));
+        assert!(close(rgb_to_hsv([0.5, 0.5, 0.5]), [0.0, 0.0, 0.5]));
+        for c in [[0.2, 0.4, 0.9], [0.9, 0.1, 0.3], [0.3, 0.8, 0.1], [1.0, 1.0, 0.0]] {
+            assert!(close(hsv_to_rgb(rgb_to_hsv(c)), c));
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        let c = Col::new(1.0, 0.0, 51.0 / 255.0, 1.0);
+        assert_eq!(to_hex(c), "#ff0033");
+        assert_eq!(to_hex(c.with_alpha(0.5)), "#ff003380");
+        assert_eq!(parse_hex("#ff0033"), Some(c));
+        assert_eq!(parse_hex(" f03 "), Some(c));
+        assert_eq!(parse_hex("ff003380").map(|c| to_u8(c.a)), Some(0x80));
+        assert_eq!(parse_hex("#ff00"), None);
+        assert_eq!(parse_hex("#gg0033"), None);
+        assert_eq!(parse_hex("+f0033"), None);
+    }
+}
@@ -0,0 +1,255 @@
+use std::any::Any;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::NamedKey;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::popup::Popup;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, lpt, lrt, lsz};
+use crate::visual::vis::UserData;
+
+const PAD_DP: f64 = 6.0;
+const ARROW_DP: f64 = 16.0;
+const MAX_ROWS: usize = 8;
+const SCROLL_DP: f64 = 30.0;
+const SEARCH_RESET: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ComboState {
+    #[serde(skip)]
+    open: bool,
+    // Option highlighted by the keyboard or mouse.
+    #[serde(skip)]
+    hi: Option<usize>,
+    // Type-to-search query and when it was last typed into.
+    #[serde(skip)]
+    search: String,
+    #[serde(skip)]
+    search_at: Option<Instant>,
+    #[serde(skip)]
+    scroll: f64,
+}
+
+#[typetag::serde]
+impl UserData for ComboState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Index of the first option starting with |query|, or failing that containing
+// it, ignoring case.
+fn search(options: &[String], query: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    let options: Vec<_> = options.iter().map(|o| o.to_lowercase()).collect();
+    options
+        .iter()
+        .position(|o| o.starts_with(&query))
+        .or_else(|| options.iter().position(|o| o.contains(&query)))
+}
+
+// Scroll offset which keeps row |i| of height |row_h| within a view of height
+// |view_h|, moving as little as possible.
+#[must_use]
+pub fn scroll_to(scroll: f64, i: usize, row_h: f64, view_h: f64) -> f64 {
+    let y = i as f64 * row_h;
+    scroll.min(y).max(y + row_h - view_h)
+}
+
+// Shows the selected option of |options| and opens a list to choose another
+// when clicked. The list can be navigated with the arrow keys, and typing
+// jumps to a matching option.
+#[derive(Debug)]
+pub struct ComboBox<'a> {
+    id: String,
+    sel: &'a mut usize,
+    options: Vec<String>,
+}
+
+impl<'a> ComboBox<'a> {
+    pub fn new(id: &str, sel: &'a mut usize, options: Vec<String>) -> Self {
+        Self { id: id.to_owned(), sel, options }
+    }
+
+    // Lists the options inside the popup. Returns the chosen option, if any.
+    fn list(&self, ui: &mut Ui<'_>, combo_id: &str, w: f64, row_h: f64) -> Result<Option<usize>> {
+        let n = self.options.len();
+        let (total_h, view_h) = (n as f64 * row_h, n.min(MAX_ROWS) as f64 * row_h);
+        let list = ui.child_layer(&Hint::make_exact(lsz(w, view_h)));
+        let list_id = combine_ids(&[ui.id(), "list"]);
+        let wheel = ui.scrolled(&list_id, list).y;
+
+        // Keyboard navigation and type-to-search.
+        let now = ui.io().begin_frame_time;
+        let (up, down) =
+            (ui.io().key_pressed(NamedKey::ArrowUp), ui.io().key_pressed(NamedKey::ArrowDown));
+        let enter = ui.io().key_pressed(NamedKey::Enter);
+        let typed = ui.io().typed();
+        let st = ui.mem_mut().user::<ComboState>(combo_id)?;
+        let mut hi = st.hi.unwrap_or(*self.sel);
+        let mut scroll = st.scroll - wheel * SCROLL_DP;
+        if up || down {
+            hi = if up { hi.saturating_sub(1) } else { (hi + 1).min(n.saturating_sub(1)) };
+            scroll = scroll_to(scroll, hi, row_h, view_h);
+        }
+        if !typed.is_empty() {
+            if st.search_at.is_some_and(|t| now - t > SEARCH_RESET) {
+                st.search.clear();
+            }
+            st.search.push_str(&typed);
+            st.search_at = Some(now);
+            if let Some(i) = search(&self.options, &st.search) {
+                hi = i;
+                scroll = scroll_to(scroll, hi, row_h, view_h);
+            }
+        }
+        st.scroll = scroll.clamp(0.0, (total_h - view_h).max(0.0));
+        let scroll = st.scroll;
+        let mut chosen = enter.then_some(hi);
+
+        let scope = ui.push();
+        scope.clip(list.r);
+        for (i, o) in self.options.iter().enumerate() {
+            let r = lrt(list.r.x, list.r.y + i as f64 * row_h - scroll, w, row_h);
+            if r.b() < list.r.y || r.y > list.r.b() {
+                continue;
+            }
+            let l = LclLayer::new(r, list.z);
+            let row_id = combine_ids(&[ui.id(), "row", &i.to_string()]);
+            if ui.hovered(&row_id, l) && !ui.io().mouse_delta.is_zero() {
+                hi = i;
+            }
+            ui.pressed(&row_id, l);
+            if ui.clicked(&row_id, l) {
+                chosen = Some(i);
+            }
+
+            if i == hi {
+                scope.col(ui.s.acc3_col.with_alpha(0.5));
+                ui.fill_rrt(r, 4.0);
+            }
+            scope.col(ui.s.light_col);
+            let f = Frag::new(o, ui.s.font_sz, lpt(r.x + PAD_DP, r.center().y));
+            ui.text(&f.vgrav(Grav::Center))?;
+        }
+        ui.mem_mut().user::<ComboState>(combo_id)?.hi = Some(hi);
+        Ok(chosen)
+    }
+}
+
+impl Widget for ComboBox<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let mut w: f64 = 0.0;
+        let mut row_h: f64 = ui.text_sz(&Frag::new(" ", ui.s.font_sz, LclPt::zero()))?.h;
+        for o in &self.options {
+            let sz = ui.text_sz(&Frag::new(o, ui.s.font_sz, LclPt::zero()))?;
+            w = w.max(sz.w);
+            row_h = row_h.max(sz.h);
+        }
+        let (w, row_h) = (w + 2.0 * PAD_DP + ARROW_DP, row_h + PAD_DP);
+        let l = ui.child_layer(&Hint::make_exact(lsz(w, row_h)));
+
+        // Decide whether to show the list before handling this frame's press,
+        // so the press which opens it doesn't also dismiss it.
+        let hovered = ui.hovered(&id, l);
+        ui.pressed(&id, l);
+        let was_open = ui.mem_mut().user::<ComboState>(&id)?.open;
+        if hovered && ui.io().mouse_just_pressed {
+            let st = ui.mem_mut().user::<ComboState>(&id)?;
+            st.open = !was_open;
+            st.hi = None;
+            st.search.clear();
+        }
+
+        let col = if hovered || was_open { 0.3 } else { 0.2 };
+        let scope = ui.push();
+        scope.col(ui.s.light_col.with_alpha(col));
+        ui.fill_rrt(l.r, 4.0);
+        scope.col(ui.s.light_col);
+        if let Some(o) = self.options.get(*self.sel) {
+            let f = Frag::new(o, ui.s.font_sz, lpt(l.r.x + PAD_DP, l.r.center().y));
+            ui.text(&f.vgrav(Grav::Center))?;
+        }
+        let (x, cy) = (l.r.r() - ARROW_DP / 2.0 - PAD_DP / 2.0, l.r.center().y);
+        ui.fill_poly(vec![lpt(x - 4.0, cy - 2.0), lpt(x + 4.0, cy - 2.0), lpt(x, cy + 3.0)]);
+        drop(scope);
+
+        if was_open {
+            let mut open = true;
+            let mut chosen = None;
+            let popup_id = combine_ids(&[&self.id, "popup"]);
+            Popup::new(&popup_id, l.r.bl(), &mut open, |ui| {
+                chosen = self.list(ui, &id, w, row_h)?;
+                Ok(())
+            })
+            .ui(ui)?;
+            if let Some(i) = chosen {
+                *self.sel = i;
+                open = false;
+            }
+            ui.mem_mut().user::<ComboState>(&id)?.open &= open;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["combo", &self.id])
+    }
+}
+
+// Chooses |value| from |options|, using their `Display` output as labels.
+pub fn combo_value<T: Display + PartialEq + Clone>(
+    ui: &mut Ui<'_>,
+    id: &str,
+    value: &mut T,
+    options: &[T],
+) -> Result<Resp> {
+    let mut sel = options.iter().position(|o| o == value).unwrap_or_default();
+    let prev = sel;
+    let labels = options.iter().map(ToString::to_string).collect();
+    let resp = ComboBox::new(id, &mut sel, labels).ui(ui)?;
+    // Only a new selection overwrites |value|, which may not be in |options|.
+    if sel != prev
+        && let Some(o) = options.get(sel)
+    {
+        value.clone_from(o);
+    }
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let options: Vec<String> =
+            ["Linear", "Nearest", "Cubic", "near"].iter().map(ToString::to_string).collect();
+        assert_eq!(search(&options, ""), None);
+        assert_eq!(search(&options, "ne"), Some(1));
+        assert_eq!(search(&options, "NEAR"), Some(1));
+        assert_eq!(search(&options, "bic"), Some(2));
+        assert_eq!(search(&options, "x"), None);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_scroll_to() {
+        assert_eq!(scroll_to(0.0, 0, 10.0, 40.0), 0.0);
+        assert_eq!(scroll_to(0.0, 5, 10.0, 40.0), 20.0);
+        assert_eq!(scroll_to(30.0, 1, 10.0, 40.0), 10.0);
+        assert_eq!(scroll_to(15.0, 3, 10.0, 40.0), 15.0);
+    }
+}
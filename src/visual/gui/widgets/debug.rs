@@ -12,5 +12,11 @@ pub fn debug_pane(ui: &mut Ui<'_>) -> Result<()> {
     ui.label(&format!("[debug] render ms: {:.2}", rt * 1000.0))?;
     ui.label(&format!("[debug] fps: {:.2}", 1.0 / rt))?;
     ui.label(&format!("[debug] z-order: {}", ui.info().gtf.z(ZOrder::zero())))?;
+    ui.log_console("[debug] log")?;
+    ui.collapsing("[debug] memory", |ui| {
+        let v = serde_json::to_value(ui.mem())?;
+        ui.json_view("[debug] memory", &v)?;
+        Ok(())
+    })?;
     Ok(())
 }
@@ -0,0 +1,304 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use eyre::Result;
+use rgb::RGBA8;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{
+    PlotArea, PlotView, Range, Ticks, ViewState, draw_axes, fmt_tick, pan_zoom, ticks,
+};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::render::texture::{Filter, TexId, TextureLayer};
+use crate::visual::types::{Col, LclSz, TexUvRect, lpt, lrt, lsz, lz, tsz};
+
+const BAR_W_DP: f64 = 12.0;
+// Room on the right for the colour bar and its labels.
+const BAR_AREA_DP: f64 = 64.0;
+const BAR_STEPS: usize = 64;
+const Y_TICK_SPACING_DP: f64 = 40.0;
+
+const VIRIDIS: [[u8; 3]; 10] = [
+    [68, 1, 84],
+    [72, 40, 120],
+    [62, 73, 137],
+    [49, 104, 142],
+    [38, 130, 142],
+    [31, 158, 137],
+    [53, 183, 121],
+    [110, 206, 88],
+    [181, 222, 43],
+    [253, 231, 37],
+];
+const MAGMA: [[u8; 3]; 10] = [
+    [0, 0, 4],
+    [24, 15, 61],
+    [68, 15, 118],
+    [114, 31, 129],
+    [158, 47, 127],
+    [205, 64, 113],
+    [241, 96, 93],
+    [253, 150, 104],
+    [254, 202, 141],
+    [252, 253, 191],
+];
+const GRAYSCALE: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+const DIVERGING: [[u8; 3]; 7] = [
+    [33, 102, 172],
+    [103, 169, 207],
+    [209, 229, 240],
+    [247, 247, 247],
+    [253, 219, 199],
+    [239, 138, 98],
+    [178, 24, 43],
+];
+
+// Maps values between zero and one to colours.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    Grayscale,
+    // Blue through white to red, for values either side of a midpoint.
+    Diverging,
+}
+
+impl Colormap {
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            Self::Viridis => &VIRIDIS,
+            Self::Magma => &MAGMA,
+            Self::Grayscale => &GRAYSCALE,
+            Self::Diverging => &DIVERGING,
+        }
+    }
+
+    // Colour at |t|, clamped to zero to one. Missing values are transparent.
+    #[must_use]
+    pub fn at(self, t: f64) -> RGBA8 {
+        if t.is_nan() {
+            return RGBA8::new(0, 0, 0, 0);
+        }
+        let stops = self.stops();
+        let f = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let i = (f.floor() as usize).min(stops.len() - 2);
+        let (a, b, k) = (stops[i], stops[i + 1], f - i as f64);
+        let lerp =
+            |c: usize| (f64::from(a[c]) + (f64::from(b[c]) - f64::from(a[c])) * k).round() as u8;
+        RGBA8::new(lerp(0), lerp(1), lerp(2), 255)
+    }
+
+    #[must_use]
+    pub fn col(self, t: f64) -> Col {
+        let c = self.at(t);
+        Col::new(
+            f32::from(c.r) / 255.0,
+            f32::from(c.g) / 255.0,
+            f32::from(c.b) / 255.0,
+            f32::from(c.a) / 255.0,
+        )
+    }
+}
+
+// Range of the finite values in |data|. Symmetric about zero for diverging
+// colormaps so zero is in the middle.
+fn value_range(data: &[f64], cmap: Colormap) -> Range {
+    let r = Range::of(data.iter().copied()).unwrap_or(Range::new(0.0, 1.0));
+    let r = if cmap == Colormap::Diverging {
+        let m = r.min.abs().max(r.max.abs());
+        Range::new(-m, m)
+    } else {
+        r
+    };
+    if r.span() > 0.0 { r } else { r.pad(0.0) }
+}
+
+// Colours of |data| through |cmap| across |r|.
+fn colorize(data: &[f64], cmap: Colormap, r: Range) -> Vec<RGBA8> {
+    data.iter()
+        .map(|&v| cmap.at(if v.is_finite() { r.map(v, 0.0, 1.0) } else { f64::NAN }))
+        .collect()
+}
+
+// Shows a matrix of values as colours, stored row by row in |data| with
+// |cols| values per row. Wheel zooms, dragging pans and right clicking resets.
+#[derive(Debug)]
+pub struct Heatmap<'a> {
+    id: String,
+    data: &'a [f64],
+    cols: usize,
+    cmap: Colormap,
+    range: Option<Range>,
+    sz: LclSz,
+}
+
+impl<'a> Heatmap<'a> {
+    #[must_use]
+    pub fn new(id: &str, data: &'a [f64], cols: usize) -> Self {
+        Self {
+            id: id.to_owned(),
+            data,
+            cols: cols.max(1),
+            cmap: Colormap::default(),
+            range: None,
+            sz: lsz(400, 300),
+        }
+    }
+
+    #[must_use]
+    pub fn colormap(self, cmap: Colormap) -> Self {
+        Self { cmap, ..self }
+    }
+
+    // Values mapped to the ends of the colormap. Defaults to the range of the
+    // data.
+    #[must_use]
+    pub fn range(self, range: Range) -> Self {
+        Self { range: Some(range), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    // Uploads the coloured data to the widget's texture if it changed.
+    fn update_tex(&self, ui: &mut Ui<'_>, id: &str, rows: usize, r: Range) -> Result<TexId> {
+        let mut h = DefaultHasher::new();
+        (self.cols, rows, self.cmap, r.min.to_bits(), r.max.to_bits()).hash(&mut h);
+        self.data.iter().for_each(|v| v.to_bits().hash(&mut h));
+        let key = h.finish();
+
+        let n = self.cols * rows;
+        ui.widget_tex(id, tsz(self.cols, rows), key, |_| {
+            Ok(colorize(&self.data[..n], self.cmap, r))
+        })
+    }
+
+    fn color_bar(&self, ui: &mut Ui<'_>, l: LclLayer, r: Range) -> Result<()> {
+        let scope = ui.push();
+        scope.z(l.z);
+        let step_h = l.r.h / BAR_STEPS as f64;
+        for i in 0..BAR_STEPS {
+            let t = (i as f64 + 0.5) / BAR_STEPS as f64;
+            scope.col(self.cmap.col(t));
+            let y = l.r.b() - (i + 1) as f64 * step_h;
+            ui.fill_rt(lrt(l.r.x, y, l.r.w, step_h + 0.5));
+        }
+        scope.col(ui.s.light_col.with_alpha(0.3));
+        ui.stroke_rt(l.r);
+
+        scope.col(ui.s.light_col.with_alpha(0.6));
+        let (ts, step) = ticks(r, (l.r.h / Y_TICK_SPACING_DP) as usize);
+        for t in ts {
+            let y = r.map(t, l.r.b(), l.r.y);
+            ui.stroke_line(lpt(l.r.r(), y), lpt(l.r.r() + 3.0, y));
+            let f = Frag::new(&fmt_tick(t, step), ui.s.font_sz, lpt(l.r.r() + 5.0, y));
+            ui.text(&f.vgrav(Grav::Center))?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for Heatmap<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let rows = self.data.len() / self.cols;
+        let full = PlotView {
+            x: Range::new(0.0, self.cols as f64),
+            y: Range::new(0.0, (rows as f64).max(1.0)),
+        };
+
+        let plot_l = LclLayer::new(lrt(l.r.x, l.r.y, l.r.w - BAR_AREA_DP, l.r.h), l.z);
+        let view = ui.mem_mut().user::<ViewState>(&id)?.view;
+        let mut a = PlotArea::new(plot_l, view.unwrap_or(full));
+        let hovered = pan_zoom(ui, &id, &mut a);
+        if ui.rclicked(&id, a.l) {
+            a.view = full;
+        }
+        ui.mem_mut().user::<ViewState>(&id)?.view = Some(a.view);
+
+        let r = self.range.unwrap_or_else(|| value_range(self.data, self.cmap));
+        draw_axes(ui, &a, Ticks::Values, Ticks::Descending(full.y.max))?;
+        if rows > 0 {
+            // One quad for the whole matrix, clipped to the plot area.
+            let tex = self.update_tex(ui, &id, rows, r)?;
+            let gr = ui.info().gtf.rt(a.rt_to_scr(full.x, full.y));
+            let scope = ui.push();
+            scope.z(a.l.z + lz(1)).clip(a.l.r);
+            ui.tex(
+                TextureLayer::new(gr, TexUvRect::new(0.0, 0.0, 1.0, 1.0), tex)
+                    .with_filter(Filter::Nearest),
+            );
+        }
+
+        let bar = lrt(plot_l.r.r() + BAR_W_DP, a.l.r.y, BAR_W_DP, a.l.r.h);
+        self.color_bar(ui, LclLayer::new(bar, l.z), r)?;
+
+        // Row 0 is at the top, so rows count down from the top of the view.
+        // Casts saturate, so check the range before trusting |col| and |row|.
+        let [x, y] = a.from_scr(ui.info().gtf.inv().pt(ui.io().mouse_pt));
+        let (col, row) = (x.floor() as usize, (rows as f64 - y).floor() as usize);
+        if hovered
+            && !ui.io().is_mouse_pressed
+            && x >= 0.0
+            && y >= 0.0
+            && y < rows as f64
+            && col < self.cols
+            && row < rows
+        {
+            let cell = a.rt_to_scr(
+                Range::new(col as f64, (col + 1) as f64),
+                Range::new((rows - row - 1) as f64, (rows - row) as f64),
+            );
+            let scope = ui.push();
+            scope.z(a.l.z + lz(2)).clip(a.l.r).col(ui.s.light_col);
+            ui.stroke_rt(cell);
+            drop(scope);
+            let v = self.data[row * self.cols + col];
+            ui.tooltip(&format!("[{row}, {col}]: {v}"))?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["heatmap", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colormap() {
+        assert_eq!(Colormap::Grayscale.at(0.0), RGBA8::new(0, 0, 0, 255));
+        assert_eq!(Colormap::Grayscale.at(0.5), RGBA8::new(128, 128, 128, 255));
+        assert_eq!(Colormap::Grayscale.at(2.0), RGBA8::new(255, 255, 255, 255));
+        assert_eq!(Colormap::Viridis.at(1.0), RGBA8::new(253, 231, 37, 255));
+        assert_eq!(Colormap::Diverging.at(0.5), RGBA8::new(247, 247, 247, 255));
+        assert_eq!(Colormap::Magma.at(f64::NAN).a, 0);
+    }
+
+    #[test]
+    fn test_value_range() {
+        let data = [1.0, -3.0, 2.0, f64::NAN];
+        assert_eq!(value_range(&data, Colormap::Viridis), Range::new(-3.0, 2.0));
+        assert_eq!(value_range(&data, Colormap::Diverging), Range::new(-3.0, 3.0));
+        assert_eq!(value_range(&[5.0], Colormap::Viridis), Range::new(4.5, 5.5));
+        assert_eq!(value_range(&[], Colormap::Viridis), Range::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_colorize() {
+        let c = colorize(&[0.0, 10.0, f64::INFINITY], Colormap::Grayscale, Range::new(0.0, 10.0));
+        assert_eq!(
+            c,
+            [RGBA8::new(0, 0, 0, 255), RGBA8::new(255, 255, 255, 255), RGBA8::new(0, 0, 0, 0)]
+        );
+    }
+}
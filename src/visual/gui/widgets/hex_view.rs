@@ -0,0 +1,331 @@
+use std::any::Any;
+use std::fmt::Write;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::combo::scroll_to;
+use crate::visual::gui::widgets::text_edit::{draw_text_field, edit_buf, edit_text};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, LclSz, lpt, lrt, lsz, lz};
+use crate::visual::vis::{MONO, UserData};
+
+const PAD_DP: f64 = 4.0;
+const BAR_DP: f64 = 22.0;
+const GAP_DP: f64 = 12.0;
+const SCROLL_DP: f64 = 40.0;
+const GOTO_DP: f64 = 140.0;
+const INTERP_DP: f64 = 260.0;
+// Bytes per group, which are separated by an extra space.
+const GROUP: usize = 8;
+
+// Selected range as where it was started and where it ends.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HexState {
+    #[serde(skip)]
+    sel_range: Option<(usize, usize)>,
+    #[serde(skip)]
+    scroll: f64,
+}
+
+#[typetag::serde]
+impl UserData for HexState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Offset typed into the goto field, in hex with a leading "0x" and decimal
+// otherwise.
+fn parse_offset(s: &str) -> Option<usize> {
+    let s = s.trim();
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+// Column of byte |i| of a row in the hex pane, in characters.
+fn hex_col(i: usize) -> usize {
+    i * 3 + i / GROUP
+}
+
+// Byte of a row of |cols| under column |c| of the hex pane, or the nearest
+// one.
+fn hex_byte(c: f64, cols: usize) -> usize {
+    (0..cols).rev().find(|&i| hex_col(i) as f64 <= c).unwrap_or_default()
+}
+
+// The first bytes of |b| read as each integer and float type, little and big
+// endian. Types wider than |b| are left out.
+fn interpret(b: &[u8]) -> Vec<(&'static str, String, String)> {
+    let mut rows = Vec::new();
+    macro_rules! read {
+        ($($t:ty),*) => {$(
+            if let Some(b) = b.get(..size_of::<$t>()).and_then(|b| b.try_into().ok()) {
+                let (le, be) = (<$t>::from_le_bytes(b), <$t>::from_be_bytes(b));
+                rows.push((stringify!($t), le.to_string(), be.to_string()));
+            }
+        )*};
+    }
+    read!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+    rows
+}
+
+fn ascii(b: u8) -> char {
+    if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }
+}
+
+// Offsets, bytes in hex and as ASCII of |data|, in a monospace font. Only
+// rows in view are drawn, so it copes with large buffers. Selecting in
+// either pane highlights both, and the selection is read as numbers in a
+// panel to the right.
+#[derive(Debug)]
+pub struct HexView<'a> {
+    id: String,
+    data: &'a [u8],
+    cols: usize,
+    sz: LclSz,
+}
+
+impl<'a> HexView<'a> {
+    #[must_use]
+    pub fn new(id: &str, data: &'a [u8]) -> Self {
+        Self { id: id.to_owned(), data, cols: 16, sz: lsz(760, 400) }
+    }
+
+    // Bytes per row.
+    #[must_use]
+    pub fn cols(self, cols: usize) -> Self {
+        Self { cols: cols.max(1), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    fn mono(ui: &Ui<'_>, text: &str, p: LclPt) -> Frag {
+        Frag::new(text, ui.s.font_sz, p).font(MONO).vgrav(Grav::Center)
+    }
+
+    // Goto field. Returns the offset entered, if any.
+    fn goto(ui: &mut Ui<'_>, id: &str, l: LclLayer) -> Result<Option<usize>> {
+        let mut buf = std::mem::take(edit_buf(ui, id)?);
+        let st = edit_text(ui, id, l, &mut buf);
+        draw_text_field(ui, l, st, &buf, "goto offset")?;
+        let off = if st.committed { parse_offset(&buf) } else { None };
+        if st.committed || st.cancelled {
+            buf.clear();
+        }
+        *edit_buf(ui, id)? = buf;
+        Ok(off)
+    }
+
+    fn draw_interp(
+        ui: &mut Ui<'_>,
+        l: LclLayer,
+        sel: Option<(usize, usize)>,
+        data: &[u8],
+    ) -> Result<()> {
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rrt(l.r, 3.0);
+        scope.clip(l.r);
+        let Some((start, end)) = sel else {
+            scope.col(ui.s.light_col.with_alpha(0.6));
+            ui.text(
+                &Frag::new("Select bytes to interpret", ui.s.font_sz, l.r.center())
+                    .hgrav(Grav::Center)
+                    .vgrav(Grav::Center),
+            )?;
+            return Ok(());
+        };
+        let h = ui.text_sz(&Self::mono(ui, "0", LclPt::zero()))?.h + 2.0;
+        let (x, w) = (l.r.x + PAD_DP, l.r.w - 2.0 * PAD_DP);
+        let mut y = l.r.y + PAD_DP + h / 2.0;
+        scope.col(ui.s.light_col.with_alpha(0.6));
+        let n = end - start + 1;
+        ui.text(&Self::mono(
+            ui,
+            &format!("at {start:#x}, {n} byte{}", if n == 1 { "" } else { "s" }),
+            lpt(x, y),
+        ))?;
+        y += h;
+        let cols = [(x + 0.35 * w, "LE"), (x + w, "BE")];
+        for (cx, label) in cols {
+            ui.text(&Self::mono(ui, label, lpt(cx, y)).hgrav(Grav::End))?;
+        }
+        for (name, le, be) in interpret(&data[start..]) {
+            y += h;
+            scope.col(ui.s.light_col.with_alpha(0.6));
+            ui.text(&Self::mono(ui, name, lpt(x, y)))?;
+            scope.col(ui.s.light_col);
+            for ((cx, _), v) in cols.iter().zip([le, be]) {
+                ui.text(&Self::mono(ui, &v, lpt(*cx, y)).hgrav(Grav::End))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Widget for HexView<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let (n, cols) = (self.data.len(), self.cols);
+        let rows = n.div_ceil(cols);
+        let csz = ui.text_sz(&Self::mono(ui, "0", LclPt::zero()))?;
+        let (cw, row_h) = (csz.w, csz.h + 2.0);
+        let bar = lrt(l.r.x, l.r.y, l.r.w, BAR_DP);
+        let body_y = bar.b() + PAD_DP;
+        let body_h = (l.r.h - BAR_DP - PAD_DP).max(0.0);
+        let interp = LclLayer::new(lrt(l.r.r() - INTERP_DP, body_y, INTERP_DP, body_h), l.z);
+        let view = lrt(l.r.x, body_y, (l.r.w - INTERP_DP - PAD_DP).max(0.0), body_h);
+
+        // Columns: offset, hex bytes, then ASCII.
+        let off_digits = format!("{:x}", n.saturating_sub(1)).len().max(8);
+        let hex_x = view.x + PAD_DP + off_digits as f64 * cw + GAP_DP;
+        let ascii_x = hex_x + (hex_col(cols - 1) + 2) as f64 * cw + GAP_DP;
+
+        let view_l = LclLayer::new(view, l.z + lz(1));
+        let wheel = ui.scrolled(&id, view_l).y;
+        let h = rows as f64 * row_h;
+        let goto_l = LclLayer::new(lrt(bar.x, bar.y, GOTO_DP, bar.h), l.z + lz(1));
+        let goto = Self::goto(ui, &combine_ids(&[&id, "goto"]), goto_l)?
+            .filter(|_| n > 0)
+            .map(|off| off.min(n - 1));
+
+        // Selecting: press to start, drag to extend, shift-click to extend
+        // from the start.
+        ui.hovered(&id, view_l);
+        let pressed = ui.pressed(&id, view_l);
+        let mouse = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+        let (just_pressed, shift) = (ui.io().mouse_just_pressed, ui.io().mods.shift_key());
+        let st = ui.mem_mut().user::<HexState>(&id)?;
+        st.scroll = (st.scroll - wheel * SCROLL_DP).clamp(0.0, (h - view.h).max(0.0));
+        if let Some(off) = goto {
+            st.scroll = scroll_to(st.scroll, off / cols, row_h, view.h);
+            st.sel_range = Some((off, off));
+        }
+        let scroll = st.scroll;
+        if pressed && n > 0 {
+            let row = ((mouse.y - view.y + scroll) / row_h).floor().max(0.0) as usize;
+            let col = if mouse.x < ascii_x - GAP_DP / 2.0 {
+                hex_byte((mouse.x - hex_x) / cw, cols)
+            } else {
+                (((mouse.x - ascii_x) / cw).max(0.0) as usize).min(cols - 1)
+            };
+            let at = (row * cols + col).min(n - 1);
+            st.sel_range = match st.sel_range {
+                Some((anchor, _)) if !just_pressed || shift => Some((anchor, at)),
+                _ => Some((at, at)),
+            };
+        }
+        let sel = st.sel_range.filter(|&(a, b)| a.max(b) < n).map(|(a, b)| (a.min(b), a.max(b)));
+
+        // Selected range in the toolbar.
+        if let Some((start, end)) = sel {
+            let scope = ui.push();
+            scope.z(l.z).col(ui.s.light_col.with_alpha(0.6));
+            let text = format!("{start:#x}..={end:#x} ({} bytes)", end - start + 1);
+            ui.text(&Self::mono(ui, &text, lpt(goto_l.r.r() + GAP_DP, bar.center().y)))?;
+        }
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rrt(view, 3.0);
+        scope.z(l.z + lz(1)).clip(view);
+        let first = (scroll / row_h).floor() as usize;
+        let last = (((scroll + view.h) / row_h).ceil() as usize).min(rows);
+        for row in first..last {
+            let y = view.y + row as f64 * row_h - scroll;
+            let cy = y + row_h / 2.0;
+            let start = row * cols;
+            let bytes = &self.data[start..(start + cols).min(n)];
+
+            // Highlight the selection in both panes.
+            if let Some((a, b)) = sel
+                && a < start + bytes.len()
+                && b >= start
+            {
+                let (a, b) = (a.max(start) - start, b.min(start + bytes.len() - 1) - start);
+                scope.col(ui.s.acc3_col.with_alpha(0.4));
+                let hx0 = hex_x + hex_col(a) as f64 * cw - cw / 2.0;
+                let hx1 = hex_x + (hex_col(b) + 2) as f64 * cw + cw / 2.0;
+                ui.fill_rt(lrt(hx0, y, hx1 - hx0, row_h));
+                ui.fill_rt(lrt(ascii_x + a as f64 * cw, y, (b - a + 1) as f64 * cw, row_h));
+            }
+
+            let mut hex = String::with_capacity(hex_col(cols));
+            for (i, b) in bytes.iter().enumerate() {
+                hex.push_str(if i > 0 && i % GROUP == 0 {
+                    "  "
+                } else if i > 0 {
+                    " "
+                } else {
+                    ""
+                });
+                write!(hex, "{b:02x}")?;
+            }
+            let text: String = bytes.iter().map(|&b| ascii(b)).collect();
+            scope.col(ui.s.light_col.with_alpha(0.5));
+            ui.text(&Self::mono(ui, &format!("{start:0off_digits$x}"), lpt(view.x + PAD_DP, cy)))?;
+            scope.col(ui.s.light_col);
+            ui.text(&Self::mono(ui, &hex, lpt(hex_x, cy)))?;
+            ui.text(&Self::mono(ui, &text, lpt(ascii_x, cy)))?;
+        }
+        if h > view.h {
+            let th = (view.h / h * view.h).max(row_h);
+            let ty = view.y + scroll / (h - view.h) * (view.h - th);
+            scope.col(ui.s.light_col.with_alpha(0.3));
+            ui.fill_rrt(lrt(view.r() - 6.0, ty, 4.0, th), 2.0);
+        }
+        drop(scope);
+
+        Self::draw_interp(ui, interp, sel, self.data)?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["hex_view", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset(" 0x1F "), Some(31));
+        assert_eq!(parse_offset("16"), Some(16));
+        assert_eq!(parse_offset("0xg"), None);
+        assert_eq!(parse_offset(""), None);
+    }
+
+    #[test]
+    fn test_hex_byte() {
+        assert_eq!(hex_col(8), 25);
+        assert_eq!(hex_byte(-1.0, 16), 0);
+        assert_eq!(hex_byte(4.5, 16), 1);
+        assert_eq!(hex_byte(24.5, 16), 7);
+        assert_eq!(hex_byte(25.0, 16), 8);
+        assert_eq!(hex_byte(100.0, 16), 15);
+    }
+
+    #[test]
+    fn test_interpret() {
+        let rows = interpret(&[0x01, 0x02, 0x00, 0x00]);
+        let names: Vec<_> = rows.iter().map(|r| r.0).collect();
+        assert_eq!(names, ["u8", "i8", "u16", "i16", "u32", "i32", "f32"]);
+        assert_eq!(rows[2], ("u16", "513".to_owned(), "258".to_owned()));
+        assert_eq!(rows[4], ("u32", "513".to_owned(), "16908288".to_owned()));
+        assert_eq!(interpret(&[0xff])[1], ("i8", "-1".to_owned(), "-1".to_owned()));
+    }
+}
@@ -0,0 +1,110 @@
+use eyre::Result;
+
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::render::texture::{Filter, TexId, TextureLayer};
+use crate::visual::types::{LclRt, LclSz, TexUvRect, lrt, lsz};
+
+// How an image is scaled to the size of its widget.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Scaling {
+    // As large as fits, keeping the aspect ratio.
+    #[default]
+    Fit,
+    // Covers the whole widget, keeping the aspect ratio and cropping the
+    // edges.
+    Fill,
+    // Covers the whole widget, ignoring the aspect ratio.
+    Stretch,
+}
+
+// Rect to draw an image of size |img| in |r|, and the part of the image to
+// show there.
+fn place(img: LclSz, r: LclRt, scaling: Scaling) -> (LclRt, TexUvRect) {
+    let full = TexUvRect::new(0.0, 0.0, 1.0, 1.0);
+    if img.w <= 0.0 || img.h <= 0.0 || r.w <= 0.0 || r.h <= 0.0 {
+        return (r, full);
+    }
+    let (sx, sy) = (r.w / img.w, r.h / img.h);
+    match scaling {
+        Scaling::Fit => {
+            let s = sx.min(sy);
+            let (w, h) = (img.w * s, img.h * s);
+            (lrt(r.x + (r.w - w) / 2.0, r.y + (r.h - h) / 2.0, w, h), full)
+        }
+        Scaling::Fill => {
+            let s = sx.max(sy);
+            let (fw, fh) = (r.w / (img.w * s), r.h / (img.h * s));
+            (r, TexUvRect::new((1.0 - fw) / 2.0, (1.0 - fh) / 2.0, fw, fh))
+        }
+        Scaling::Stretch => (r, full),
+    }
+}
+
+// Shows a texture, e.g. one created with `Ui::create_tex`. Sized to the
+// texture in dp unless given a size.
+#[derive(Debug)]
+pub struct Image {
+    tex: TexId,
+    sz: Option<LclSz>,
+    scaling: Scaling,
+    filter: Filter,
+}
+
+impl Image {
+    #[must_use]
+    pub fn new(tex: TexId) -> Self {
+        Self { tex, sz: None, scaling: Scaling::default(), filter: Filter::default() }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz: Some(sz), ..self }
+    }
+
+    #[must_use]
+    pub fn scaling(self, scaling: Scaling) -> Self {
+        Self { scaling, ..self }
+    }
+
+    #[must_use]
+    pub fn filter(self, filter: Filter) -> Self {
+        Self { filter, ..self }
+    }
+}
+
+impl Widget for Image {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let img = ui.tex_sz(self.tex)?.to_f64();
+        let img = lsz(img.w, img.h);
+        let l = ui.child_layer(&Hint::make_exact(self.sz.unwrap_or(img)));
+        let (r, uv) = place(img, l.r, self.scaling);
+        let gr = ui.info().gtf.rt(r);
+        let scope = ui.push();
+        scope.z(l.z).clip(l.r);
+        ui.tex(TextureLayer::new(gr, uv, self.tex).with_filter(self.filter));
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["image", &self.tex.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place() {
+        let r = lrt(10, 0, 200, 100);
+        let full = TexUvRect::new(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(place(lsz(50, 50), r, Scaling::Fit), (lrt(60, 0, 100, 100), full));
+        assert_eq!(place(lsz(400, 100), r, Scaling::Fit), (lrt(10, 25, 200, 50), full));
+        assert_eq!(place(lsz(50, 50), r, Scaling::Fill), (r, TexUvRect::new(0.0, 0.25, 1.0, 0.5)));
+        assert_eq!(place(lsz(50, 50), r, Scaling::Stretch), (r, full));
+        assert_eq!(place(lsz(0, 50), r, Scaling::Fit), (r, full));
+    }
+}
@@ -0,0 +1,293 @@
+use std::any::Any;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use eyre::Result;
+use num_traits::Zero;
+use rgb::RGBA8;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{PlotView, Range, pan_zoom_input};
+use crate::visual::gui::widgets::button::{tool_button, tool_button_w};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::render::texture::{Filter, TexId, TextureLayer};
+use crate::visual::types::{LclPt, LclRt, LclSz, TexUvRect, lpt, lrt, lsz, lz, tpt};
+use crate::visual::vis::UserData;
+
+const STATUS_DP: f64 = 22.0;
+const PAD_DP: f64 = 6.0;
+const ZOOM_STEP: f64 = 1.2;
+// Zoom at which the pixel grid is shown, in dp per pixel.
+const GRID_SCALE: f64 = 8.0;
+
+// Channels of an image to show.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Channel {
+    #[default]
+    All,
+    R,
+    G,
+    B,
+    A,
+}
+
+impl Channel {
+    const ALL: [Self; 5] = [Self::All, Self::R, Self::G, Self::B, Self::A];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::All => "RGBA",
+            Self::R => "R",
+            Self::G => "G",
+            Self::B => "B",
+            Self::A => "A",
+        }
+    }
+
+    // Only this channel of |c|, as an opaque grey.
+    #[must_use]
+    pub fn isolate(self, c: RGBA8) -> RGBA8 {
+        let v = match self {
+            Self::All => return c,
+            Self::R => c.r,
+            Self::G => c.g,
+            Self::B => c.b,
+            Self::A => c.a,
+        };
+        RGBA8::new(v, v, v, 255)
+    }
+}
+
+// View of an image of size |img| in pixels fitting inside |r|, centred and
+// with square pixels. Y counts down from the top of the image.
+fn fit_view(img: LclSz, r: LclRt) -> PlotView {
+    if img.w <= 0.0 || img.h <= 0.0 {
+        return PlotView { x: Range::new(0.0, 1.0), y: Range::new(0.0, 1.0) };
+    }
+    let s = (r.w / img.w).min(r.h / img.h);
+    let (w, h) = (r.w / s, r.h / s);
+    PlotView {
+        x: Range::new(f64::midpoint(img.w, -w), f64::midpoint(img.w, w)),
+        y: Range::new(f64::midpoint(img.h, -h), f64::midpoint(img.h, h)),
+    }
+}
+
+// Maps between image pixels and the screen for |view| shown in |r|.
+fn to_scr(view: &PlotView, r: LclRt, x: f64, y: f64) -> LclPt {
+    lpt(view.x.map(x, r.x, r.r()), view.y.map(y, r.y, r.b()))
+}
+
+fn from_scr(view: &PlotView, r: LclRt, p: LclPt) -> [f64; 2] {
+    [view.x.unmap(p.x, r.x, r.r()), view.y.unmap(p.y, r.y, r.b())]
+}
+
+// View of an image and the channel shown.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ViewerState {
+    view: Option<PlotView>,
+    ch: Channel,
+}
+
+#[typetag::serde]
+impl UserData for ViewerState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Inspects a texture. Wheel zooms about the cursor, dragging pans and right
+// clicking fits the image again. Shows the pixel under the cursor, a grid of
+// pixels when zoomed in, and buttons to show single channels.
+#[derive(Debug)]
+pub struct ImageViewer {
+    id: String,
+    tex: TexId,
+    sz: LclSz,
+}
+
+impl ImageViewer {
+    #[must_use]
+    pub fn new(id: &str, tex: TexId) -> Self {
+        Self { id: id.to_owned(), tex, sz: lsz(400, 300) }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    // Like `axis::pan_zoom`, but with y pointing down and both axes zoomed
+    // together so pixels stay square.
+    fn pan_zoom(ui: &mut Ui<'_>, id: &str, l: LclLayer, view: &mut PlotView) -> bool {
+        let pz = pan_zoom_input(ui, id, l);
+        let r = l.r;
+        if !pz.wheel.is_zero() {
+            let [x, y] = from_scr(view, r, pz.mouse);
+            let f = ZOOM_STEP.powf(-pz.wheel);
+            *view = PlotView { x: view.x.zoom(x, f), y: view.y.zoom(y, f) };
+        }
+        *view = PlotView {
+            x: view.x.shift(-pz.drag.w / r.w * view.x.span()),
+            y: view.y.shift(-pz.drag.h / r.h * view.y.span()),
+        };
+        pz.active
+    }
+
+    // Texture with only |ch| of the image, remade when the image changes.
+    fn isolated(&self, ui: &mut Ui<'_>, id: &str, ch: Channel) -> Result<TexId> {
+        if ch == Channel::All {
+            ui.free_widget_tex(id);
+            return Ok(self.tex);
+        }
+        let mut h = DefaultHasher::new();
+        (self.tex, ui.tex_generation(self.tex)?, ch).hash(&mut h);
+        let sz = ui.tex_sz(self.tex)?;
+        ui.widget_tex(id, sz, h.finish(), |ui| {
+            Ok(ui.read_tex(self.tex)?.data.into_iter().map(|c| ch.isolate(c)).collect())
+        })
+    }
+
+    // Channel buttons from the right of |l|. Returns the selected channel.
+    fn channel_btns(
+        ui: &mut Ui<'_>,
+        id: &str,
+        l: LclLayer,
+        mut ch: Channel,
+    ) -> Result<(Channel, f64)> {
+        let (r, mut x) = (l.r, l.r.r());
+        for c in Channel::ALL.into_iter().rev() {
+            let w = tool_button_w(ui, c.label())?;
+            x -= w;
+            let l = LclLayer::new(lrt(x, r.y + 2.0, w, r.h - 4.0), l.z + lz(1));
+            if tool_button(ui, &combine_ids(&[id, c.label()]), l, c.label(), c == ch)? {
+                ch = c;
+            }
+            x -= 2.0;
+        }
+        Ok((ch, x))
+    }
+}
+
+impl Widget for ImageViewer {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let img = ui.tex_sz(self.tex)?.to_f64();
+        let img = lsz(img.w, img.h);
+        let r = lrt(l.r.x, l.r.y, l.r.w, (l.r.h - STATUS_DP).max(1.0));
+        let vl = LclLayer::new(r, l.z);
+
+        let mut view =
+            ui.mem_mut().user::<ViewerState>(&id)?.view.unwrap_or_else(|| fit_view(img, r));
+        let hovered = Self::pan_zoom(ui, &id, vl, &mut view);
+        if ui.rclicked(&id, vl) {
+            view = fit_view(img, r);
+        }
+        let st = ui.mem_mut().user::<ViewerState>(&id)?;
+        st.view = Some(view);
+        let (ch, scale) = (st.ch, r.w / view.x.span());
+
+        let status = lrt(l.r.x, r.b(), l.r.w, STATUS_DP);
+        let (ch, btns_x) = Self::channel_btns(ui, &id, LclLayer::new(status, l.z), ch)?;
+        ui.mem_mut().user::<ViewerState>(&id)?.ch = ch;
+        let tex = self.isolated(ui, &id, ch)?;
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col);
+        ui.fill_rt(r);
+
+        // Only draw the part of the image in view, so huge zooms stay exact.
+        let (x0, x1) = (view.x.min.max(0.0), view.x.max.min(img.w));
+        let (y0, y1) = (view.y.min.max(0.0), view.y.max.min(img.h));
+        if x1 > x0 && y1 > y0 {
+            let (p0, p1) = (to_scr(&view, r, x0, y0), to_scr(&view, r, x1, y1));
+            let sr = LclRt::ptsz(p0, (p1 - p0).to_sz());
+            // Texture rows are stored bottom first.
+            let uv = TexUvRect::new(
+                x0 / img.w,
+                (img.h - y1) / img.h,
+                (x1 - x0) / img.w,
+                (y1 - y0) / img.h,
+            );
+            let filter = if scale >= 1.0 { Filter::Nearest } else { Filter::Linear };
+            scope.z(l.z + lz(1)).clip(r);
+            ui.tex(TextureLayer::new(ui.info().gtf.rt(sr), uv, tex).with_filter(filter));
+
+            if scale >= GRID_SCALE {
+                scope.z(l.z + lz(2)).col(ui.s.dark_col.with_alpha(0.5));
+                for x in x0.ceil() as i64..=x1.floor() as i64 {
+                    let x = to_scr(&view, r, x as f64, 0.0).x;
+                    ui.stroke_line(lpt(x, p0.y), lpt(x, p1.y));
+                }
+                for y in y0.ceil() as i64..=y1.floor() as i64 {
+                    let y = to_scr(&view, r, 0.0, y as f64).y;
+                    ui.stroke_line(lpt(p0.x, y), lpt(p1.x, y));
+                }
+            }
+        }
+
+        // Readout of the pixel under the cursor, from the original texture.
+        let [mx, my] = from_scr(&view, r, ui.info().gtf.inv().pt(ui.io().mouse_pt));
+        let mut text = String::new();
+        if hovered && mx >= 0.0 && my >= 0.0 && mx < img.w && my < img.h {
+            let p = tpt(mx.floor() as u32, my.floor() as u32);
+            if let Some(c) = ui.tex_px(self.tex, p)? {
+                text = format!(
+                    "{}, {}  ({}, {}, {}, {})  #{:02x}{:02x}{:02x}{:02x}",
+                    p.x, p.y, c.r, c.g, c.b, c.a, c.r, c.g, c.b, c.a
+                );
+            }
+            if scale >= GRID_SCALE / 2.0 {
+                let (fx, fy) = (mx.floor(), my.floor());
+                let (p0, p1) = (to_scr(&view, r, fx, fy), to_scr(&view, r, fx + 1.0, fy + 1.0));
+                scope.z(l.z + lz(3)).col(ui.s.light_col);
+                ui.stroke_rt(LclRt::ptsz(p0, (p1 - p0).to_sz()));
+            }
+        }
+        drop(scope);
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.light_col).clip(lrt(status.x, status.y, btns_x - status.x, status.h));
+        let cy = status.center().y;
+        ui.text(&Frag::new(&text, ui.s.font_sz, lpt(status.x + PAD_DP, cy)).vgrav(Grav::Center))?;
+        let zoom = format!("{:.0}%", scale * 100.0);
+        ui.text(
+            &Frag::new(&zoom, ui.s.font_sz, lpt(btns_x - PAD_DP, cy))
+                .hgrav(Grav::End)
+                .vgrav(Grav::Center),
+        )?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["image_viewer", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_fit_view() {
+        let r = lrt(0, 0, 200, 100);
+        let v = fit_view(lsz(50, 50), r);
+        assert_eq!(v.x, Range::new(-25.0, 75.0));
+        assert_eq!(v.y, Range::new(0.0, 50.0));
+        assert_eq!(to_scr(&v, r, 0.0, 0.0), lpt(50, 0));
+        assert_eq!(from_scr(&v, r, lpt(150, 100)), [50.0, 50.0]);
+    }
+
+    #[test]
+    fn test_isolate() {
+        let c = RGBA8::new(1, 2, 3, 4);
+        assert_eq!(Channel::All.isolate(c), c);
+        assert_eq!(Channel::G.isolate(c), RGBA8::new(2, 2, 2, 255));
+        assert_eq!(Channel::A.isolate(c), RGBA8::new(4, 4, 4, 255));
+    }
+}
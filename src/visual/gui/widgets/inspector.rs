@@ -0,0 +1,395 @@
+use std::any::Any;
+
+use ahash::HashMap;
+use eyre::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::collapse::{CollapsingHeader, INDENT_DP, indented};
+use crate::visual::gui::widgets::combo::ComboBox;
+use crate::visual::gui::widgets::text_edit::{EditState, draw_text_field, edit_buf, edit_text};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{lpt, lrt, lsz};
+use crate::visual::vis::UserData;
+
+const ROW_DP: f64 = 22.0;
+const KEY_DP: f64 = 120.0;
+const PAD_DP: f64 = 4.0;
+const CHECK_DP: f64 = 14.0;
+// Change per dp dragged for floats. Integers change by one per dp.
+const DRAG_SPEED: f64 = 0.01;
+// Put in place of a value to find out whether it's an enum. Can't be a
+// variant name since it isn't an identifier.
+const PROBE: &str = "\u{1}probe";
+
+// Appends |key| to the JSON pointer |ptr|.
+#[must_use]
+pub fn ptr_join(ptr: &str, key: &str) -> String {
+    format!("{ptr}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+// Variant names listed in a serde "unknown variant" error for |PROBE|, e.g.
+// "unknown variant `x`, expected one of `A`, `B`, `C`".
+fn parse_variants(err: &str) -> Option<Vec<String>> {
+    let rest = err.split_once(&format!("unknown variant `{PROBE}`"))?.1;
+    Some(rest.split('`').skip(1).step_by(2).map(ToOwned::to_owned).collect())
+}
+
+// Variants of the enum at |ptr| in |root|, if it holds one. Serde doesn't
+// describe types, so this swaps in an unknown variant and reads the names
+// out of the error deserializing |T|.
+fn variants<T: DeserializeOwned>(root: &Value, ptr: &str) -> Option<Vec<String>> {
+    let mut probe = root.clone();
+    *probe.pointer_mut(ptr)? = Value::String(PROBE.to_owned());
+    let err = serde_json::from_value::<T>(probe).err()?;
+    parse_variants(&err.to_string())
+}
+
+// Variant name of the enum value |v|: a unit variant is a string and other
+// variants are an object with a single key.
+fn variant(v: &Value) -> Option<&str> {
+    match v {
+        Value::String(s) => Some(s),
+        Value::Object(m) if m.len() == 1 => m.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+// |n| moved by |dx| dp of dragging. Integers stay integers. Serde writes
+// non-negative signed integers the same as unsigned ones, so integers may go
+// negative here and deserializing an unsigned type rejects them.
+fn drag_num(n: &Number, dx: f64) -> Option<Number> {
+    let step = dx.round() as i64;
+    if let Some(i) = n.as_i64() {
+        Some(Number::from(i.saturating_add(step)))
+    } else if let Some(u) = n.as_u64() {
+        // Too big for an i64, so unsigned.
+        Some(Number::from(u.saturating_add_signed(step)))
+    } else {
+        Number::from_f64(n.as_f64()? + dx * DRAG_SPEED)
+    }
+}
+
+// Number typed as |s|, kept an integer if |n| is one.
+fn parse_num(n: &Number, s: &str) -> Option<Number> {
+    let s = s.trim();
+    if n.is_i64() || n.is_u64() {
+        s.parse::<i64>().map(Number::from).or_else(|_| s.parse::<u64>().map(Number::from)).ok()
+    } else {
+        Number::from_f64(s.parse().ok()?)
+    }
+}
+
+// Variants of the enums in an inspected value, or None if not an enum, by
+// JSON pointer. Probing deserializes the whole value, so it's done once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct InspectorState {
+    #[serde(skip)]
+    variants: HashMap<String, Option<Vec<String>>>,
+}
+
+#[typetag::serde]
+impl UserData for InspectorState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Shows the fields of |value| as it serializes, and writes back any edits.
+// Fields are in the order they serialize in.
+// Nested structs and lists are collapsible sections, numbers are dragged or
+// clicked to type, bools are checkboxes, strings are text fields and enums are
+// combo boxes. Edits which |T| doesn't deserialize from, e.g. switching to a
+// variant which holds data, are dropped.
+#[derive(Debug)]
+pub struct Inspector<'a, T: Serialize + DeserializeOwned> {
+    id: String,
+    value: &'a mut T,
+    w: f64,
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Inspector<'a, T> {
+    pub fn new(id: &str, value: &'a mut T) -> Self {
+        Self { id: id.to_owned(), value, w: 320.0 }
+    }
+
+    #[must_use]
+    pub fn width(self, w: f64) -> Self {
+        Self { w, ..self }
+    }
+
+    // Rows for each field or element of |v| at |ptr|, |w| wide.
+    fn fields(
+        ui: &mut Ui<'_>,
+        id: &str,
+        root: &Value,
+        ptr: &str,
+        v: &mut Value,
+        w: f64,
+    ) -> Result<bool> {
+        let mut changed = false;
+        match v {
+            Value::Object(m) => {
+                for (k, v) in m.iter_mut() {
+                    changed |= Self::row(ui, id, root, &ptr_join(ptr, k), k, v, w)?;
+                }
+            }
+            Value::Array(a) => {
+                for (i, v) in a.iter_mut().enumerate() {
+                    let k = i.to_string();
+                    changed |= Self::row(ui, id, root, &ptr_join(ptr, &k), &k, v, w)?;
+                }
+            }
+            _ => changed |= Self::row(ui, id, root, ptr, "value", v, w)?,
+        }
+        Ok(changed)
+    }
+
+    // Row for |v|, labelled |key|. Returns whether |v| was edited.
+    fn row(
+        ui: &mut Ui<'_>,
+        id: &str,
+        root: &Value,
+        ptr: &str,
+        key: &str,
+        v: &mut Value,
+        w: f64,
+    ) -> Result<bool> {
+        let vid = combine_ids(&[id, ptr]);
+        let enum_variants = match variant(v) {
+            Some(_) => ui
+                .mem_mut()
+                .user::<InspectorState>(id)?
+                .variants
+                .entry(ptr.to_owned())
+                .or_insert_with(|| variants::<T>(root, ptr))
+                .clone(),
+            None => None,
+        };
+        if let Some(options) = enum_variants {
+            let mut changed = Self::enum_row(ui, &vid, key, v, &options, w)?;
+            // Fields of a variant which holds data.
+            if let Value::Object(m) = v
+                && let Some((name, inner)) = m.iter_mut().next()
+            {
+                let ptr = ptr_join(ptr, name);
+                indented(ui, |ui| {
+                    changed |= Self::fields(ui, id, root, &ptr, inner, w - INDENT_DP)?;
+                    Ok(())
+                })?;
+            }
+            return Ok(changed);
+        }
+        if matches!(v, Value::Object(_) | Value::Array(_)) {
+            let mut changed = false;
+            CollapsingHeader::new(key, |ui| {
+                changed |= Self::fields(ui, id, root, ptr, v, w - INDENT_DP)?;
+                Ok(())
+            })
+            .ui(ui)?;
+            return Ok(changed);
+        }
+
+        let ed = Self::key_row(ui, key, w)?;
+        match v {
+            Value::Bool(b) => Ok(Self::checkbox(ui, &vid, ed, b)),
+            Value::Number(n) => Self::number(ui, &vid, ed, n),
+            Value::String(s) => {
+                let st = edit_text(ui, &vid, ed, s);
+                draw_text_field(ui, ed, st, s, "")?;
+                Ok(st.changed)
+            }
+            _ => {
+                let scope = ui.push();
+                scope.z(ed.z).col(ui.s.light_col.with_alpha(0.4));
+                let f = Frag::new("null", ui.s.font_sz, lpt(ed.r.x + PAD_DP, ed.r.center().y));
+                ui.text(&f.vgrav(Grav::Center))?;
+                Ok(false)
+            }
+        }
+    }
+
+    // Lays out a row |w| wide labelled |key|. Returns where its value goes.
+    fn key_row(ui: &mut Ui<'_>, key: &str, w: f64) -> Result<LclLayer> {
+        let l = ui.child_layer(&Hint::make_exact(lsz(w, ROW_DP)));
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.light_col.with_alpha(0.7)).clip(lrt(l.r.x, l.r.y, KEY_DP, l.r.h));
+        ui.text(&Frag::new(key, ui.s.font_sz, lpt(l.r.x, l.r.center().y)).vgrav(Grav::Center))?;
+        let r = lrt(l.r.x + KEY_DP, l.r.y + 1.0, (w - KEY_DP).max(0.0), ROW_DP - 2.0);
+        Ok(LclLayer::new(r, l.z))
+    }
+
+    fn enum_row(
+        ui: &mut Ui<'_>,
+        vid: &str,
+        key: &str,
+        v: &mut Value,
+        options: &[String],
+        w: f64,
+    ) -> Result<bool> {
+        let ed = Self::key_row(ui, key, w)?;
+        let cur = variant(v).unwrap_or_default();
+        let mut sel = options.iter().position(|o| o == cur).unwrap_or_default();
+        let prev = sel;
+        ui.place(ed, vid, |ui| {
+            ComboBox::new(vid, &mut sel, options.to_vec()).ui(ui)?;
+            Ok(())
+        })?;
+        if sel == prev {
+            return Ok(false);
+        }
+        *v = Value::String(options[sel].clone());
+        Ok(true)
+    }
+
+    fn checkbox(ui: &mut Ui<'_>, vid: &str, ed: LclLayer, b: &mut bool) -> bool {
+        let r = lrt(ed.r.x, ed.r.center().y - CHECK_DP / 2.0, CHECK_DP, CHECK_DP);
+        let l = LclLayer::new(r, ed.z);
+        let hovered = ui.hovered(vid, l);
+        ui.pressed(vid, l);
+        let clicked = ui.clicked(vid, l);
+        if clicked {
+            *b = !*b;
+        }
+
+        let scope = ui.push();
+        let alpha = if hovered { 0.3 } else { 0.15 };
+        scope.z(l.z).col(ui.s.light_col.with_alpha(alpha));
+        ui.fill_rrt(r, 3.0);
+        if *b {
+            let c = r.center();
+            scope.col(ui.s.acc3_col).line_width(2.0);
+            ui.stroke_line(lpt(c.x - 4.0, c.y), lpt(c.x - 1.0, c.y + 3.0));
+            ui.stroke_line(lpt(c.x - 1.0, c.y + 3.0), lpt(c.x + 4.0, c.y - 4.0));
+        }
+        clicked
+    }
+
+    // Drags to change |n|, or types a new value after a click.
+    fn number(ui: &mut Ui<'_>, vid: &str, ed: LclLayer, n: &mut Number) -> Result<bool> {
+        if ui.focused(vid) {
+            let mut buf = std::mem::take(edit_buf(ui, vid)?);
+            let st = edit_text(ui, vid, ed, &mut buf);
+            draw_text_field(ui, ed, st, &buf, "")?;
+            let typed = if st.committed { parse_num(n, &buf) } else { None };
+            *edit_buf(ui, vid)? = buf;
+            let Some(typed) = typed else { return Ok(false) };
+            *n = typed;
+            return Ok(true);
+        }
+
+        let hovered = ui.hovered(vid, ed);
+        let pressed = ui.pressed(vid, ed);
+        let io = ui.io();
+        let still = (io.mouse_pt - io.mouse_pressed_pt).x.abs() < 2.0;
+        let gtf = ui.info().gtf;
+        let dx = gtf.inv().pt(io.mouse_pt).x - gtf.inv().pt(io.mouse_pt - io.mouse_delta.to_sz()).x;
+        let mut changed = false;
+        if ui.clicked(vid, ed) && still {
+            ui.focus(vid);
+            *edit_buf(ui, vid)? = n.to_string();
+        } else if pressed
+            && !still
+            && dx != 0.0
+            && let Some(m) = drag_num(n, dx)
+        {
+            changed = m != *n;
+            *n = m;
+        }
+        let st = EditState { hovered: hovered || pressed, ..EditState::default() };
+        draw_text_field(ui, ed, st, &n.to_string(), "")?;
+        Ok(changed)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Widget for Inspector<'_, T> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let root = serde_json::to_value(&*self.value)?;
+        let mut v = root.clone();
+        let mut changed = false;
+        let w = self.w;
+        let l = ui.child(
+            &Hint::new(),
+            &id,
+            |info| Layout::new(VertLayout::new(info)),
+            |ui| {
+                changed = Self::fields(ui, &id, &root, "", &mut v, w)?;
+                Ok(())
+            },
+        )?;
+        if changed && let Ok(value) = serde_json::from_value(v) {
+            *self.value = value;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["inspector", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    enum Filter {
+        Linear,
+        Nearest,
+        Cubic(f64),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Settings {
+        name: String,
+        filter: Filter,
+        others: Vec<Filter>,
+    }
+
+    #[test]
+    fn test_variants() {
+        let s = Settings {
+            name: "Linear".to_owned(),
+            filter: Filter::Cubic(0.5),
+            others: vec![Filter::Nearest],
+        };
+        let root = serde_json::to_value(&s).unwrap();
+        let all = Some(vec!["Linear".to_owned(), "Nearest".to_owned(), "Cubic".to_owned()]);
+        assert_eq!(variants::<Settings>(&root, "/name"), None);
+        assert_eq!(variants::<Settings>(&root, "/filter"), all);
+        assert_eq!(variants::<Settings>(&root, "/others/0"), all);
+        assert_eq!(variants::<Settings>(&root, "/missing"), None);
+        assert_eq!(
+            parse_variants("unknown variant `\u{1}probe`, expected `A` or `B`").unwrap(),
+            ["A", "B"]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_num() {
+        let (u, i, f) = (Number::from(1u64), Number::from(-1i64), Number::from_f64(1.0).unwrap());
+        assert_eq!(drag_num(&u, -3.0), Some(Number::from(-2i64)));
+        assert_eq!(drag_num(&i, 2.4), Some(Number::from(1i64)));
+        assert_eq!(drag_num(&f, 10.0).and_then(|n| n.as_f64()), Some(1.1));
+        let big = Number::from(u64::MAX);
+        assert_eq!(drag_num(&big, -1.0), Some(Number::from(u64::MAX - 1)));
+        assert_eq!(parse_num(&u, " 7 "), Some(Number::from(7u64)));
+        assert_eq!(parse_num(&u, "-7"), Some(Number::from(-7i64)));
+        assert_eq!(parse_num(&u, "1.5"), None);
+        assert_eq!(parse_num(&f, "2.5").and_then(|n| n.as_f64()), Some(2.5));
+
+        // Only unsigned types reject going below zero.
+        let dragged = Value::Number(drag_num(&u, -3.0).unwrap());
+        assert_eq!(serde_json::from_value::<i32>(dragged.clone()).ok(), Some(-2));
+        assert!(serde_json::from_value::<u32>(dragged).is_err());
+    }
+}
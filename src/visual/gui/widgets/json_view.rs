@@ -0,0 +1,433 @@
+use std::any::Any;
+
+use ahash::HashMap;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::button::{tool_button, tool_button_w};
+use crate::visual::gui::widgets::collapse::{INDENT_DP, disclosure};
+use crate::visual::gui::widgets::inspector::ptr_join;
+use crate::visual::gui::widgets::text_edit::filter_field;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{Col, LclRt, LclSz, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const ROW_DP: f64 = 22.0;
+const PAD_DP: f64 = 4.0;
+const SCROLL_DP: f64 = 40.0;
+const SEARCH_DP: f64 = 180.0;
+// Arrays longer than this are split into groups of this many elements, which
+// are only flattened when opened.
+const CHUNK: usize = 100;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    // An object, array or group of elements, and whether it's open.
+    Open(bool),
+    Str,
+    Num,
+    Bool,
+    Null,
+}
+
+// A row of the flattened tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    depth: usize,
+    key: String,
+    // JSONPath of the value, e.g. `$.a[3].b`.
+    path: String,
+    // Identifies the row to remember whether it's open.
+    ptr: String,
+    text: String,
+    kind: Kind,
+}
+
+// JSONPath of |key| in the object at |path|.
+fn path_key(path: &str, key: &str) -> String {
+    let ident = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if ident {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}['{}']", key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+// Text and kind of a value shown on one row. Objects and arrays are
+// summarised by their size.
+fn summary(v: &Value) -> (String, Kind) {
+    let plural = |n: usize, what: &str| format!("{n} {what}{}", if n == 1 { "" } else { "s" });
+    match v {
+        Value::Object(m) => (format!("{{}} {}", plural(m.len(), "key")), Kind::Open(false)),
+        Value::Array(a) => (format!("[] {}", plural(a.len(), "item")), Kind::Open(false)),
+        Value::String(_) => (v.to_string(), Kind::Str),
+        Value::Number(_) => (v.to_string(), Kind::Num),
+        Value::Bool(_) => (v.to_string(), Kind::Bool),
+        Value::Null => (v.to_string(), Kind::Null),
+    }
+}
+
+// Search matches by row pointer: true if the key or value of the row
+// matches, and false if something inside it does.
+type Found = HashMap<String, bool>;
+
+// Adds what matches |query|, which is lowercase, in |v| at |ptr| to |found|.
+// Returns whether anything did.
+fn find_in(key: &str, v: &Value, ptr: &str, query: &str, found: &mut Found) -> bool {
+    let hit = |s: &str| s.to_lowercase().contains(query);
+    let mut inner = false;
+    match v {
+        Value::Object(m) => {
+            for (k, v) in m {
+                inner |= find_in(k, v, &ptr_join(ptr, k), query, found);
+            }
+        }
+        Value::Array(a) => {
+            for (i, v) in a.iter().enumerate() {
+                let k = i.to_string();
+                // Long arrays are split into groups, which are in the pointer.
+                let group = (a.len() > CHUNK).then(|| format!("{ptr}#{}", i / CHUNK * CHUNK));
+                if find_in(&k, v, &ptr_join(group.as_deref().unwrap_or(ptr), &k), query, found) {
+                    inner = true;
+                    if let Some(group) = group {
+                        found.insert(group, false);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    let leaf = !matches!(v, Value::Object(_) | Value::Array(_));
+    let self_hit = hit(key) || (leaf && hit(&v.to_string()));
+    if self_hit || inner {
+        found.insert(ptr.to_owned(), self_hit);
+    }
+    self_hit || inner
+}
+
+// Rows of |v| matching |query|, or holding something that does.
+fn find(v: &Value, query: &str) -> Found {
+    let mut found = Found::default();
+    find_in("$", v, "", &query.to_lowercase(), &mut found);
+    found
+}
+
+// Flattens the open parts of a value into rows. While searching, only shows
+// matches and what leads to them, opening the way there.
+struct Walk<'a> {
+    found: Option<&'a Found>,
+    open: &'a mut dyn FnMut(&str) -> Option<bool>,
+    rows: Vec<Row>,
+}
+
+impl Walk<'_> {
+    fn node(&mut self, key: &str, v: &Value, path: &str, ptr: &str, depth: usize, search: bool) {
+        let (text, kind) = summary(v);
+        let leaf = !matches!(kind, Kind::Open(_));
+        let found = if search { self.found.and_then(|f| f.get(ptr).copied()) } else { None };
+        if search && found.is_none() {
+            return;
+        }
+        let self_hit = found == Some(true);
+        let inner_hit = found == Some(false);
+        let mut row = Row {
+            depth,
+            key: key.to_owned(),
+            path: path.to_owned(),
+            ptr: ptr.to_owned(),
+            text,
+            kind,
+        };
+        if leaf {
+            self.rows.push(row);
+            return;
+        }
+        let open = inner_hit || (self.open)(ptr).unwrap_or(depth == 0);
+        row.kind = Kind::Open(open);
+        self.rows.push(row);
+        if !open {
+            return;
+        }
+        // Everything in something which matched is shown.
+        let search = search && !self_hit;
+        match v {
+            Value::Object(m) => {
+                for (k, v) in m {
+                    self.node(k, v, &path_key(path, k), &ptr_join(ptr, k), depth + 1, search);
+                }
+            }
+            Value::Array(a) if a.len() <= CHUNK => self.elems(a, 0, path, ptr, depth + 1, search),
+            Value::Array(a) => {
+                for (i, chunk) in a.chunks(CHUNK).enumerate() {
+                    let start = i * CHUNK;
+                    let ptr = format!("{ptr}#{start}");
+                    let hit = search && self.found.is_some_and(|f| f.contains_key(&ptr));
+                    if search && !hit {
+                        continue;
+                    }
+                    let open = hit || (self.open)(&ptr).unwrap_or(false);
+                    self.rows.push(Row {
+                        depth: depth + 1,
+                        key: format!("[{start} .. {}]", start + chunk.len() - 1),
+                        path: path.to_owned(),
+                        ptr: ptr.clone(),
+                        text: String::new(),
+                        kind: Kind::Open(open),
+                    });
+                    if open {
+                        self.elems(chunk, start, path, &ptr, depth + 2, search);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Elements |items| of the array at |path|, numbered from |start|.
+    fn elems(
+        &mut self,
+        items: &[Value],
+        start: usize,
+        path: &str,
+        ptr: &str,
+        depth: usize,
+        search: bool,
+    ) {
+        for (i, v) in items.iter().enumerate() {
+            let i = (start + i).to_string();
+            self.node(&i, v, &format!("{path}[{i}]"), &ptr_join(ptr, &i), depth, search);
+        }
+    }
+}
+
+// Rows for the open parts of |v|, or what was |found| by a search if given.
+// |open| gives whether a row was opened or closed, by its pointer.
+fn flatten(
+    v: &Value,
+    found: Option<&Found>,
+    open: &mut dyn FnMut(&str) -> Option<bool>,
+) -> Vec<Row> {
+    let mut walk = Walk { found, open, rows: Vec::new() };
+    walk.node("$", v, "$", "", 0, found.is_some());
+    walk.rows
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct JsonState {
+    // Rows which were opened or closed, by pointer.
+    open: HashMap<String, bool>,
+    // Path of the selected row.
+    sel: Option<String>,
+    // Last search and what it found.
+    #[serde(skip)]
+    found: Option<(String, Found)>,
+    #[serde(skip)]
+    scroll: f64,
+}
+
+#[typetag::serde]
+impl UserData for JsonState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Read-only tree view of a JSON value. Objects, arrays and long runs of
+// elements open and close, and clicking a row selects its path to copy. A
+// search is done once when typed, so changes to the value while searching
+// show when the search changes.
+#[derive(Debug)]
+pub struct JsonView<'a> {
+    id: String,
+    v: &'a Value,
+    sz: LclSz,
+}
+
+impl<'a> JsonView<'a> {
+    #[must_use]
+    pub fn new(id: &str, v: &'a Value) -> Self {
+        Self { id: id.to_owned(), v, sz: lsz(500, 400) }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    fn kind_col(ui: &Ui<'_>, kind: Kind) -> Col {
+        match kind {
+            Kind::Open(_) | Kind::Null => ui.s.light_col.with_alpha(0.5),
+            Kind::Str => ui.s.acc1_col,
+            Kind::Num => ui.s.acc3_col,
+            Kind::Bool => ui.s.acc2_col,
+        }
+    }
+
+    fn draw_row(ui: &mut Ui<'_>, r: LclRt, row: &Row) -> Result<()> {
+        let scope = ui.push();
+        let cy = r.center().y;
+        let x = r.x + PAD_DP + row.depth as f64 * INDENT_DP;
+        if let Kind::Open(open) = row.kind {
+            scope.col(ui.s.light_col.with_alpha(0.7));
+            disclosure(ui, lpt(x + INDENT_DP / 2.0, cy), open);
+        }
+        let key = Frag::new(&row.key, ui.s.font_sz, lpt(x + INDENT_DP, cy)).vgrav(Grav::Center);
+        let key_w = ui.text_sz(&key)?.w;
+        scope.col(ui.s.light_col);
+        ui.text(&key)?;
+        if row.text.is_empty() {
+            return Ok(());
+        }
+        let x = x + INDENT_DP + key_w;
+        ui.text(&Frag::new(":", ui.s.font_sz, lpt(x, cy)).vgrav(Grav::Center))?;
+        scope.col(Self::kind_col(ui, row.kind));
+        ui.text(&Frag::new(&row.text, ui.s.font_sz, lpt(x + 2.0 * PAD_DP, cy)).vgrav(Grav::Center))
+    }
+}
+
+impl Widget for JsonView<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let bar = lrt(l.r.x, l.r.y, l.r.w, ROW_DP);
+        let view = lrt(l.r.x, bar.b() + PAD_DP, l.r.w, (l.r.h - ROW_DP - PAD_DP).max(0.0));
+        let bl = |x: f64, w: f64| LclLayer::new(lrt(x, bar.y, w, bar.h), l.z + lz(1));
+
+        // Toolbar: search, then the selected path and a button to copy it.
+        let search =
+            filter_field(ui, &combine_ids(&[&id, "search"]), bl(bar.x, SEARCH_DP), "search")?;
+        let sel = ui.mem_mut().user::<JsonState>(&id)?.sel.clone();
+        let copy_w = tool_button_w(ui, "Copy path")?;
+        let copy_l = bl(bar.r() - copy_w, copy_w);
+        if tool_button(ui, &combine_ids(&[&id, "copy"]), copy_l, "Copy path", false)?
+            && let Some(path) = &sel
+        {
+            ui.copy_text(path)?;
+        }
+        if let Some(path) = &sel {
+            let x = bar.x + SEARCH_DP + PAD_DP;
+            let scope = ui.push();
+            scope.z(l.z).col(ui.s.light_col.with_alpha(0.6)).clip(lrt(
+                x,
+                bar.y,
+                (copy_l.r.x - PAD_DP - x).max(0.0),
+                bar.h,
+            ));
+            ui.text(&Frag::new(path, ui.s.font_sz, lpt(x, bar.center().y)).vgrav(Grav::Center))?;
+        }
+
+        let st = ui.mem_mut().user::<JsonState>(&id)?;
+        if search.is_empty() {
+            st.found = None;
+        } else if st.found.as_ref().is_none_or(|(q, _)| *q != search) {
+            st.found = Some((search.clone(), find(self.v, &search)));
+        }
+        let found = st.found.as_ref().map(|(_, f)| f);
+        let rows = flatten(self.v, found, &mut |ptr| st.open.get(ptr).copied());
+        let n = rows.len();
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rrt(view, 3.0);
+        drop(scope);
+        let wheel = ui.scrolled(&id, LclLayer::new(view, l.z)).y;
+        let h = n as f64 * ROW_DP;
+        let st = ui.mem_mut().user::<JsonState>(&id)?;
+        st.scroll = (st.scroll - wheel * SCROLL_DP).clamp(0.0, (h - view.h).max(0.0));
+        let scroll = st.scroll;
+
+        // Only rows in view are drawn.
+        let scope = ui.push();
+        scope.z(l.z + lz(1)).clip(view);
+        let first = (scroll / ROW_DP).floor() as usize;
+        let last = (((scroll + view.h) / ROW_DP).ceil() as usize).min(n);
+        for (i, row) in rows.iter().enumerate().take(last).skip(first) {
+            let r = lrt(view.x, view.y + i as f64 * ROW_DP - scroll, view.w, ROW_DP);
+            let rl = LclLayer::new(r, l.z + lz(1));
+            let row_id = combine_ids(&[&id, &row.ptr]);
+            let hovered = ui.hovered(&row_id, rl);
+            ui.pressed(&row_id, rl);
+            if ui.clicked(&row_id, rl) {
+                let st = ui.mem_mut().user::<JsonState>(&id)?;
+                if let Kind::Open(open) = row.kind {
+                    st.open.insert(row.ptr.clone(), !open);
+                }
+                st.sel = Some(row.path.clone());
+            }
+            if sel.as_deref() == Some(row.path.as_str()) && !row.text.is_empty() {
+                scope.col(ui.s.acc3_col.with_alpha(0.4));
+                ui.fill_rt(r);
+            } else if hovered {
+                scope.col(ui.s.light_col.with_alpha(0.1));
+                ui.fill_rt(r);
+            }
+            Self::draw_row(ui, r, row)?;
+        }
+        if h > view.h {
+            let th = (view.h / h * view.h).max(ROW_DP);
+            let ty = view.y + scroll / (h - view.h) * (view.h - th);
+            scope.col(ui.s.light_col.with_alpha(0.3));
+            ui.fill_rrt(lrt(view.r() - 6.0, ty, 4.0, th), 2.0);
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["json_view", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn keys(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(|r| r.key.as_str()).collect()
+    }
+
+    #[test]
+    fn test_path_key() {
+        assert_eq!(path_key("$", "a_1"), "$.a_1");
+        assert_eq!(path_key("$.a", "x y"), "$.a['x y']");
+        assert_eq!(path_key("$", "it's"), "$['it\\'s']");
+        assert_eq!(path_key("$", "1a"), "$['1a']");
+    }
+
+    #[test]
+    fn test_flatten() {
+        let v = json!({"a": [1, {"b": "x"}], "c": null});
+        let rows = flatten(&v, None, &mut |_| None);
+        assert_eq!(keys(&rows), ["$", "a", "c"]);
+        assert_eq!(rows[1].kind, Kind::Open(false));
+        assert_eq!(rows[1].text, "[] 2 items");
+
+        let rows = flatten(&v, None, &mut |_| Some(true));
+        assert_eq!(keys(&rows), ["$", "a", "0", "1", "b", "c"]);
+        assert_eq!(rows[4].path, "$.a[1].b");
+        assert_eq!(rows[4].text, "\"x\"");
+        assert_eq!(rows[4].depth, 3);
+
+        // Searching opens the way to matches, whatever was open before.
+        let rows = flatten(&v, Some(&find(&v, "X")), &mut |_| Some(false));
+        assert_eq!(keys(&rows), ["$", "a", "1", "b"]);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let v = json!({ "a": (0..250).collect::<Vec<_>>() });
+        let rows = flatten(&v, None, &mut |ptr| Some(matches!(ptr, "" | "/a" | "/a#200")));
+        assert_eq!(keys(&rows)[..5], ["$", "a", "[0 .. 99]", "[100 .. 199]", "[200 .. 249]"]);
+        assert_eq!(rows.len(), 5 + 50);
+        assert_eq!(rows.last().map(|r| r.path.as_str()), Some("$.a[249]"));
+
+        let rows = flatten(&v, Some(&find(&v, "123")), &mut |_| None);
+        assert_eq!(keys(&rows), ["$", "a", "[100 .. 199]", "123"]);
+    }
+}
@@ -25,7 +25,7 @@ impl Widget for Label {
         let sz = ui.text_sz(&f)?;
         let l = ui.child_layer(&Hint::make_exact(sz));
         ui.text(&f.pt(l.r.tl()))?;
-        Ok(Resp { l })
+        Ok(Resp { id: ui.wid(self), l })
     }
 
     fn lcl_id(&self, _ui: &Ui<'_>) -> String {
@@ -0,0 +1,239 @@
+use std::any::Any;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const DEFAULT_DP: f64 = 300.0;
+const SCROLL_DP: f64 = 30.0;
+const BAR_DP: f64 = 6.0;
+const MIN_THUMB_DP: f64 = 20.0;
+
+// Height of the rows of a list, either the same for all rows or measured per
+// row. Measuring visits every row each frame, so prefer fixed heights for
+// very long lists.
+pub enum RowHeight<'a> {
+    Fixed(f64),
+    Measure(Box<dyn FnMut(usize) -> f64 + 'a>),
+}
+
+impl std::fmt::Debug for RowHeight<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fixed(h) => f.debug_tuple("Fixed").field(h).finish(),
+            Self::Measure(_) => f.write_str("Measure"),
+        }
+    }
+}
+
+// Vertical extents of the rows of a list.
+#[derive(Debug, Clone, PartialEq)]
+enum Extents {
+    Fixed { h: f64, n: usize },
+    // Top of each row, followed by the total height.
+    Measured(Vec<f64>),
+}
+
+impl Extents {
+    fn new(n: usize, row_h: &mut RowHeight<'_>) -> Self {
+        match row_h {
+            RowHeight::Fixed(h) => Self::Fixed { h: *h, n },
+            RowHeight::Measure(f) => {
+                let mut ys = Vec::with_capacity(n + 1);
+                let mut y = 0.0;
+                ys.push(y);
+                for i in 0..n {
+                    y += f(i);
+                    ys.push(y);
+                }
+                Self::Measured(ys)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Fixed { n, .. } => *n,
+            Self::Measured(ys) => ys.len() - 1,
+        }
+    }
+
+    // Top of row |i|. The bottom of the last row if |i| is the row count.
+    fn y(&self, i: usize) -> f64 {
+        match self {
+            Self::Fixed { h, n } => i.min(*n) as f64 * h,
+            Self::Measured(ys) => ys[i.min(ys.len() - 1)],
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.y(self.len())
+    }
+
+    // Row containing |y|, clamped to the rows.
+    fn index_at(&self, y: f64) -> usize {
+        let i = match self {
+            Self::Fixed { h, .. } if *h > 0.0 => (y / h).floor().max(0.0) as usize,
+            Self::Fixed { .. } => 0,
+            Self::Measured(ys) => ys.partition_point(|&v| v <= y).saturating_sub(1),
+        };
+        i.min(self.len().saturating_sub(1))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ListState {
+    #[serde(skip)]
+    scroll: f64,
+    // Number of rows last frame.
+    #[serde(skip)]
+    count: usize,
+}
+
+#[typetag::serde]
+impl UserData for ListState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// A scrolled list of |count| rows which only lays out the rows in view, so
+// it stays fast with millions of rows.
+#[derive(Debug)]
+pub struct List<'a, F: FnMut(&mut Ui<'_>, usize) -> Result<()>> {
+    id: String,
+    count: usize,
+    row_h: RowHeight<'a>,
+    f: F,
+    sz: (Option<f64>, Option<f64>),
+    inserts_at_top: bool,
+}
+
+impl<'a, F: FnMut(&mut Ui<'_>, usize) -> Result<()>> List<'a, F> {
+    pub fn new(id: &str, count: usize, row_h: RowHeight<'a>, f: F) -> Self {
+        Self { id: id.to_owned(), count, row_h, f, sz: (None, None), inserts_at_top: false }
+    }
+
+    // Size of the list. Defaults to the space available.
+    #[must_use]
+    pub fn width(self, w: f64) -> Self {
+        Self { sz: (Some(w), self.sz.1), ..self }
+    }
+
+    #[must_use]
+    pub fn height(self, h: f64) -> Self {
+        Self { sz: (self.sz.0, Some(h)), ..self }
+    }
+
+    // New rows are added at the top, e.g. newest first. Rows in view stay in
+    // place as rows are added, unless scrolled to the very top.
+    #[must_use]
+    pub fn inserts_at_top(self, inserts_at_top: bool) -> Self {
+        Self { inserts_at_top, ..self }
+    }
+
+    fn scrollbar(ui: &mut Ui<'_>, id: &str, view: LclLayer, total: f64, scroll: f64) -> f64 {
+        if total <= view.r.h {
+            return scroll;
+        }
+        let thumb_h = (view.r.h * view.r.h / total).max(MIN_THUMB_DP);
+        let range = view.r.h - thumb_h;
+        let y = view.r.y + range * scroll / (total - view.r.h);
+        let thumb = LclLayer::new(lrt(view.r.r() - BAR_DP, y, BAR_DP, thumb_h), view.z + lz(1));
+        let thumb_id = combine_ids(&[id, "thumb"]);
+        let hovered = ui.hovered(&thumb_id, thumb);
+        let pressed = ui.pressed(&thumb_id, thumb);
+
+        let scope = ui.push();
+        let alpha = if hovered || pressed { 0.6 } else { 0.3 };
+        scope.z(thumb.z).col(ui.s.light_col.with_alpha(alpha));
+        ui.fill_rrt(thumb.r, BAR_DP / 2.0);
+        if pressed { scroll + ui.io().mouse_delta.y * (total - view.r.h) / range } else { scroll }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>, usize) -> Result<()>> Widget for List<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let ext = Extents::new(self.count, &mut self.row_h);
+        let total = ext.total();
+        let max = ui.info().hint.max;
+        let w = self.sz.0.or(max.map(|m| m.w)).unwrap_or(DEFAULT_DP);
+        let h = self.sz.1.unwrap_or_else(|| total.min(max.map_or(DEFAULT_DP, |m| m.h)));
+        let view = ui.child_layer(&Hint::make_exact(lsz(w, h)));
+
+        // Scroll with the wheel anywhere over the list, including its rows.
+        ui.hovered(&id, view);
+        let io = ui.io();
+        let inside = io.has_mouse.as_deref().is_some_and(|m| m.starts_with(&id));
+        let wheel = if inside { io.mouse_scroll.y } else { 0.0 };
+        let count = self.count;
+        let st = ui.mem_mut().user::<ListState>(&id)?;
+        let mut scroll = st.scroll - wheel * SCROLL_DP;
+        if self.inserts_at_top && st.count > 0 && st.count < count && scroll > 0.0 {
+            scroll += ext.y(count - st.count);
+        }
+        st.count = count;
+        let scroll = Self::scrollbar(ui, &id, view, total, scroll);
+        let scroll = scroll.clamp(0.0, (total - h).max(0.0));
+        ui.mem_mut().user::<ListState>(&id)?.scroll = scroll;
+
+        if count > 0 {
+            // Keep rows clear of the scrollbar.
+            let row_w = if total > h { w - BAR_DP } else { w };
+            let scope = ui.push();
+            scope.clip(view.r);
+            let last = ext.index_at(scroll + h);
+            for i in ext.index_at(scroll)..=last {
+                let y = view.r.y + ext.y(i) - scroll;
+                let row_h = ext.y(i + 1) - ext.y(i);
+                let l = LclLayer::new(lrt(view.r.x, y, row_w, row_h), view.z);
+                let row_id = combine_ids(&[&id, &i.to_string()]);
+                ui.place(l, &row_id, |ui| (self.f)(ui, i))?;
+            }
+        }
+        Ok(Resp { id, l: view })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["list", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_fixed_extents() {
+        let ext = Extents::new(10, &mut RowHeight::Fixed(20.0));
+        assert_eq!(ext.total(), 200.0);
+        assert_eq!(ext.y(3), 60.0);
+        assert_eq!(ext.index_at(0.0), 0);
+        assert_eq!(ext.index_at(59.0), 2);
+        assert_eq!(ext.index_at(60.0), 3);
+        assert_eq!(ext.index_at(1000.0), 9);
+        assert_eq!(ext.index_at(-5.0), 0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_measured_extents() {
+        let mut row_h = RowHeight::Measure(Box::new(|i| (i + 1) as f64 * 10.0));
+        let ext = Extents::new(4, &mut row_h);
+        assert_eq!(ext, Extents::Measured(vec![0.0, 10.0, 30.0, 60.0, 100.0]));
+        assert_eq!(ext.total(), 100.0);
+        assert_eq!(ext.index_at(9.0), 0);
+        assert_eq!(ext.index_at(10.0), 1);
+        assert_eq!(ext.index_at(59.0), 2);
+        assert_eq!(ext.index_at(100.0), 3);
+        assert_eq!(Extents::new(0, &mut row_h).index_at(5.0), 0);
+    }
+}
@@ -0,0 +1,242 @@
+use std::any::Any;
+use std::str::FromStr;
+
+use eyre::Result;
+use log::{Level, LevelFilter};
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::logs::{self, LogEntry};
+use crate::visual::colors::RED;
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::button::{tool_button, tool_button_w};
+use crate::visual::gui::widgets::text_edit::filter_field;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{Col, LclRt, LclSz, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const ROW_DP: f64 = 22.0;
+const PAD_DP: f64 = 4.0;
+const SCROLL_DP: f64 = 40.0;
+const TIME_DP: f64 = 64.0;
+const LEVEL_DP: f64 = 48.0;
+const TARGET_DP: f64 = 140.0;
+const TARGET_FIELD_DP: f64 = 120.0;
+const LEVELS: [LevelFilter; 5] = [
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ConsoleState {
+    // Minimum level shown, by name.
+    level: String,
+    // Records pushed when last drawn, to keep drawing while new ones arrive.
+    #[serde(skip)]
+    pushed: u64,
+    // Scroll offset and number of rows shown last frame.
+    #[serde(skip)]
+    scroll: f64,
+    #[serde(skip)]
+    count: usize,
+}
+
+#[typetag::serde]
+impl UserData for ConsoleState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Scroll offset after new rows arrive. Follows the end if it was showing it
+// before, and otherwise stays put.
+fn follow_scroll(scroll: f64, prev_h: f64, h: f64, view_h: f64) -> f64 {
+    let at_end = scroll >= prev_h - view_h - 1.0;
+    let scroll = if at_end { h - view_h } else { scroll };
+    scroll.clamp(0.0, (h - view_h).max(0.0))
+}
+
+// Shows records captured by `logs::init`, newest at the bottom. Filters by
+// minimum level, target and message, and keeps the newest record in view
+// while scrolled to the end.
+#[derive(Debug)]
+pub struct LogConsole {
+    id: String,
+    sz: LclSz,
+}
+
+impl LogConsole {
+    #[must_use]
+    pub fn new(id: &str) -> Self {
+        Self { id: id.to_owned(), sz: lsz(600, 300) }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    fn level_col(ui: &Ui<'_>, level: Level) -> Col {
+        match level {
+            Level::Error => RED,
+            Level::Warn => ui.s.acc2_col,
+            Level::Info => ui.s.light_col,
+            Level::Debug | Level::Trace => ui.s.light_col.with_alpha(0.6),
+        }
+    }
+
+    fn draw_row(ui: &mut Ui<'_>, r: LclRt, e: &LogEntry, shade: bool) -> Result<()> {
+        let scope = ui.push();
+        if shade {
+            scope.col(ui.s.light_col.with_alpha(0.04));
+            ui.fill_rt(r);
+        }
+        let cy = r.center().y;
+        let mut x = r.x + PAD_DP;
+        let time = format!("{:.3}", e.time.as_secs_f64());
+        let level = e.level.to_string();
+        let target_col = ui.s.light_col.with_alpha(0.6);
+        let cols = [
+            (time.as_str(), TIME_DP, ui.s.light_col.with_alpha(0.5)),
+            (level.as_str(), LEVEL_DP, Self::level_col(ui, e.level)),
+            (e.target.as_str(), TARGET_DP, target_col),
+            (e.msg.as_str(), r.r() - x - TIME_DP - LEVEL_DP - TARGET_DP, ui.s.light_col),
+        ];
+        for (text, w, col) in cols {
+            let cell = ui.push();
+            cell.col(col).clip(lrt(x, r.y, (w - PAD_DP).max(0.0), r.h));
+            ui.text(&Frag::new(text, ui.s.font_sz, lpt(x, cy)).vgrav(Grav::Center))?;
+            x += w;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for LogConsole {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let bar = lrt(l.r.x, l.r.y, l.r.w, ROW_DP);
+        let view = lrt(l.r.x, bar.b() + PAD_DP, l.r.w, (l.r.h - ROW_DP - PAD_DP).max(0.0));
+
+        // Toolbar: minimum level, filters, then copy and clear on the right.
+        let level = &ui.mem_mut().user::<ConsoleState>(&id)?.level;
+        let mut level = LevelFilter::from_str(level).unwrap_or(LevelFilter::Trace);
+        let mut x = bar.x;
+        let bl = |x: f64, w: f64| LclLayer::new(lrt(x, bar.y, w, bar.h), l.z + lz(1));
+        for lv in LEVELS {
+            let label = lv.as_str();
+            let w = tool_button_w(ui, label)?;
+            if tool_button(ui, &combine_ids(&[&id, label]), bl(x, w), label, lv == level)? {
+                level = lv;
+            }
+            x += w + 2.0;
+        }
+        ui.mem_mut().user::<ConsoleState>(&id)?.level = level.to_string();
+        let (copy_w, clear_w) = (tool_button_w(ui, "Copy")?, tool_button_w(ui, "Clear")?);
+        let clear_x = bar.r() - clear_w;
+        let copy_x = clear_x - 2.0 - copy_w;
+        let copy =
+            tool_button(ui, &combine_ids(&[&id, "copy"]), bl(copy_x, copy_w), "Copy", false)?;
+        let clear =
+            tool_button(ui, &combine_ids(&[&id, "clear"]), bl(clear_x, clear_w), "Clear", false)?;
+        x += PAD_DP;
+        let target_l = bl(x, TARGET_FIELD_DP);
+        let target = filter_field(ui, &combine_ids(&[&id, "target"]), target_l, "target")?;
+        x += TARGET_FIELD_DP + PAD_DP;
+        let search_l = bl(x, (copy_x - PAD_DP - x).max(0.0));
+        let search = filter_field(ui, &combine_ids(&[&id, "search"]), search_l, "search")?;
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rrt(view, 3.0);
+        drop(scope);
+        let wheel = ui.scrolled(&id, LclLayer::new(view, l.z)).y;
+        let Some(mut buf) = logs::captured() else {
+            let scope = ui.push();
+            scope.z(l.z).col(ui.s.light_col.with_alpha(0.6));
+            let f = Frag::new(
+                "Logs are not captured. Call logs::init first.",
+                ui.s.font_sz,
+                view.center(),
+            );
+            ui.text(&f.hgrav(Grav::Center).vgrav(Grav::Center))?;
+            return Ok(Resp { id, l });
+        };
+        if clear {
+            buf.clear();
+        }
+        let pushed = buf.pushed();
+        let rows: Vec<&LogEntry> =
+            buf.iter().filter(|e| e.matches(level, &target, &search)).collect();
+        let n = rows.len();
+
+        let st = ui.mem_mut().user::<ConsoleState>(&id)?;
+        let (h, prev_h) = (n as f64 * ROW_DP, st.count as f64 * ROW_DP);
+        let scroll = if wheel.is_zero() {
+            follow_scroll(st.scroll, prev_h, h, view.h)
+        } else {
+            (st.scroll - wheel * SCROLL_DP).clamp(0.0, (h - view.h).max(0.0))
+        };
+        (st.scroll, st.count) = (scroll, n);
+
+        // Copy out what's needed so the log isn't locked while drawing, in
+        // case drawing logs something.
+        let first = (scroll / ROW_DP).floor() as usize;
+        let last = (((scroll + view.h) / ROW_DP).ceil() as usize).min(n);
+        let shown: Vec<(usize, LogEntry)> = (first..last).map(|i| (i, rows[i].clone())).collect();
+        let text =
+            copy.then(|| rows.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"));
+        drop(buf);
+        if let Some(text) = text {
+            ui.copy_text(&text)?;
+        }
+        // While records keep arriving, draw again in case more are logged
+        // after this during the frame.
+        let seen = std::mem::replace(&mut ui.mem_mut().user::<ConsoleState>(&id)?.pushed, pushed);
+        if seen != pushed {
+            ui.animate(l);
+        }
+
+        let scope = ui.push();
+        scope.z(l.z + lz(1)).clip(view);
+        for (i, e) in &shown {
+            let r = lrt(view.x, view.y + *i as f64 * ROW_DP - scroll, view.w, ROW_DP);
+            Self::draw_row(ui, r, e, i % 2 == 1)?;
+        }
+        if h > view.h {
+            let th = (view.h / h * view.h).max(ROW_DP);
+            let ty = view.y + scroll / (h - view.h) * (view.h - th);
+            scope.col(ui.s.light_col.with_alpha(0.3));
+            ui.fill_rrt(lrt(view.r() - 6.0, ty, 4.0, th), 2.0);
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["log_console", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_follow_scroll() {
+        // At the end, so follows new rows.
+        assert_eq!(follow_scroll(60.0, 100.0, 120.0, 40.0), 80.0);
+        // Scrolled up, so stays put.
+        assert_eq!(follow_scroll(20.0, 100.0, 120.0, 40.0), 20.0);
+        // Rows fit, or were cleared.
+        assert_eq!(follow_scroll(0.0, 20.0, 30.0, 40.0), 0.0);
+        assert_eq!(follow_scroll(60.0, 100.0, 0.0, 40.0), 0.0);
+    }
+}
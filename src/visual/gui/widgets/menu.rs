@@ -0,0 +1,327 @@
+use std::any::Any;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint, SzOpt};
+use crate::visual::gui::layouts::horiz_layout::HorizLayout;
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::popup::{Popup, popup_sz};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, lpt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const GUTTER_DP: f64 = 20.0;
+const SHORTCUT_GAP_DP: f64 = 24.0;
+const ROW_PAD_DP: f64 = 4.0;
+const SEPARATOR_DP: f64 = 8.0;
+
+// Lays out a menu row of at least |w| wide. The row is widened to the width
+// of the menu last frame so highlights span the whole menu.
+fn menu_row(ui: &mut Ui<'_>, w: f64, h: f64) -> Result<LclLayer> {
+    let mut l = ui.child_layer(&Hint::make_exact(lsz(w + 2.0 * GUTTER_DP, h + ROW_PAD_DP)));
+    let menu_id = ui.id().to_owned();
+    l.r.w = l.r.w.max(popup_sz(ui, &menu_id)?.w);
+    Ok(l)
+}
+
+fn highlight(ui: &mut Ui<'_>, l: LclLayer) {
+    let scope = ui.push();
+    scope.col(ui.s.acc3_col.with_alpha(0.5));
+    ui.fill_rrt(l.r, 4.0);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MenuBarState {
+    // Id of the open menu, if any.
+    #[serde(skip)]
+    open: Option<String>,
+}
+
+#[typetag::serde]
+impl UserData for MenuBarState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct MenuBar<F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    f: F,
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> MenuBar<F> {
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for MenuBar<F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child(
+            &Hint::new().opt((SzOpt::Fill, SzOpt::Wrap)),
+            &id,
+            |info| Layout::new(HorizLayout::new(info)),
+            &mut self.f,
+        )?;
+
+        let scope = ui.push();
+        scope.z(l.z - lz(1)).col(ui.s.dark_col.with_alpha(0.95));
+        ui.fill_rt(l.r);
+
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        "menu_bar".to_owned()
+    }
+}
+
+// A menu header which opens a dropdown when pressed. Inside a `MenuBar`, at
+// most one menu is open and hovering another header switches to it.
+#[derive(Debug)]
+pub struct Menu<F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    text: String,
+    f: F,
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Menu<F> {
+    pub fn new(text: &str, f: F) -> Self {
+        Self { text: text.to_owned(), f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for Menu<F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let bar_id = ui.id().to_owned();
+        let f = Frag::new(&self.text, ui.s.font_sz, LclPt::zero());
+        let sz = ui.text_sz(&f)? + lsz(GUTTER_DP, ROW_PAD_DP);
+        let l = ui.child_layer(&Hint::make_exact(sz));
+        let hovered = ui.hovered(&id, l);
+
+        // Decide whether to show the dropdown before handling this frame's
+        // press, so the press which opens it doesn't also dismiss it.
+        let open_menu = ui.mem_mut().user::<MenuBarState>(&bar_id)?.open.clone();
+        let was_open = open_menu.as_deref() == Some(id.as_str());
+        if hovered && ui.io().mouse_just_pressed {
+            ui.mem_mut().user::<MenuBarState>(&bar_id)?.open = (!was_open).then(|| id.clone());
+        } else if hovered && open_menu.is_some() && !was_open {
+            ui.mem_mut().user::<MenuBarState>(&bar_id)?.open = Some(id.clone());
+        }
+
+        if hovered || was_open {
+            highlight(ui, l);
+        }
+        ui.text(&f.pt(l.r.center()).hgrav(Grav::Center).vgrav(Grav::Center))?;
+
+        if was_open {
+            let mut open = true;
+            let popup_id = combine_ids(&[&self.text, "menu"]);
+            Popup::new(&popup_id, l.r.bl(), &mut open, &mut self.f).ui(ui)?;
+            if !open || ui.io().menu_activated {
+                let open_menu = &mut ui.mem_mut().user::<MenuBarState>(&bar_id)?.open;
+                if open_menu.as_deref() == Some(id.as_str()) {
+                    *open_menu = None;
+                }
+            }
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["menu", &self.text])
+    }
+}
+
+#[derive(Debug)]
+pub struct MenuItem<'a, F: FnOnce(&mut Ui<'_>)> {
+    text: String,
+    shortcut: Option<String>,
+    checked: Option<&'a mut bool>,
+    cb: Option<F>,
+}
+
+impl<'a, F: FnOnce(&mut Ui<'_>)> MenuItem<'a, F> {
+    pub fn new(text: &str, cb: F) -> Self {
+        Self { text: text.to_owned(), shortcut: None, checked: None, cb: Some(cb) }
+    }
+
+    // Shows a keyboard shortcut hint, e.g. "Ctrl+O". Handling the shortcut is
+    // up to the caller.
+    #[must_use]
+    pub fn shortcut(self, shortcut: &str) -> Self {
+        Self { shortcut: Some(shortcut.to_owned()), ..self }
+    }
+
+    // Makes this a checkable item which toggles |checked| when activated.
+    #[must_use]
+    pub fn checked(self, checked: &'a mut bool) -> Self {
+        Self { checked: Some(checked), ..self }
+    }
+}
+
+impl<F: FnOnce(&mut Ui<'_>)> Widget for MenuItem<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let f = Frag::new(&self.text, ui.s.font_sz, LclPt::zero());
+        let sz = ui.text_sz(&f)?;
+        let shortcut = self.shortcut.as_ref().map(|s| Frag::new(s, ui.s.font_sz, LclPt::zero()));
+        let shortcut_w = match &shortcut {
+            Some(f) => SHORTCUT_GAP_DP + ui.text_sz(f)?.w,
+            None => 0.0,
+        };
+        let l = menu_row(ui, sz.w + shortcut_w, sz.h)?;
+
+        let hovered = ui.hovered(&id, l);
+        ui.pressed(&id, l);
+        if ui.clicked(&id, l) {
+            if let Some(checked) = self.checked.as_deref_mut() {
+                *checked = !*checked;
+            }
+            if let Some(f) = self.cb.take() {
+                f(ui);
+            }
+            ui.io_mut().menu_activated = true;
+        }
+
+        if hovered {
+            highlight(ui, l);
+        }
+        let cy = l.r.center().y;
+        ui.text(&f.pt(lpt(l.r.x + GUTTER_DP, cy)).vgrav(Grav::Center))?;
+        if let Some(f) = shortcut {
+            ui.text(&f.pt(lpt(l.r.r() - GUTTER_DP, cy)).hgrav(Grav::End).vgrav(Grav::Center))?;
+        }
+        if self.checked.as_deref() == Some(&true) {
+            let x = l.r.x + GUTTER_DP / 2.0;
+            ui.stroke_line(lpt(x - 4.0, cy), lpt(x - 1.0, cy + 3.0));
+            ui.stroke_line(lpt(x - 1.0, cy + 3.0), lpt(x + 4.0, cy - 4.0));
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["menu_item", &self.text])
+    }
+}
+
+// A menu row which opens a nested menu to its right while hovered.
+#[derive(Debug)]
+pub struct SubMenu<F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    text: String,
+    f: F,
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> SubMenu<F> {
+    pub fn new(text: &str, f: F) -> Self {
+        Self { text: text.to_owned(), f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for SubMenu<F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let f = Frag::new(&self.text, ui.s.font_sz, LclPt::zero());
+        let sz = ui.text_sz(&f)?;
+        let l = menu_row(ui, sz.w + SHORTCUT_GAP_DP, sz.h)?;
+        let hovered = ui.hovered(&id, l);
+
+        let mut open = true;
+        let popup_id = combine_ids(&[&self.text, "submenu"]);
+        let mut popup = Popup::new(&popup_id, l.r.tr(), &mut open, &mut self.f);
+        let popup_id = ui.wid(&popup);
+        let inside = ui.io().has_mouse.as_deref().is_some_and(|m| m.starts_with(&popup_id));
+        if hovered || inside {
+            highlight(ui, l);
+            popup.ui(ui)?;
+        }
+
+        let cy = l.r.center().y;
+        ui.text(&f.pt(lpt(l.r.x + GUTTER_DP, cy)).vgrav(Grav::Center))?;
+        let x = l.r.r() - GUTTER_DP / 2.0;
+        ui.fill_poly(vec![lpt(x - 3.0, cy - 4.0), lpt(x + 3.0, cy), lpt(x - 3.0, cy + 4.0)]);
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["submenu", &self.text])
+    }
+}
+
+#[derive(Debug)]
+pub struct Separator;
+
+impl Widget for Separator {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = menu_row(ui, 0.0, SEPARATOR_DP - ROW_PAD_DP)?;
+        let cy = l.r.center().y;
+        let scope = ui.push();
+        scope.col(ui.s.light_col.with_alpha(0.3));
+        ui.stroke_line(lpt(l.r.x + GUTTER_DP / 2.0, cy), lpt(l.r.r() - GUTTER_DP / 2.0, cy));
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        "separator".to_owned()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ContextMenuState {
+    // Where the menu was opened, if it's open.
+    #[serde(skip)]
+    pt: Option<LclPt>,
+}
+
+#[typetag::serde]
+impl UserData for ContextMenuState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// A menu opened by right clicking on |target|, at the point it was clicked.
+#[derive(Debug)]
+pub struct ContextMenu<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    target: &'a Resp,
+    f: F,
+}
+
+impl<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> ContextMenu<'a, F> {
+    pub fn new(target: &'a Resp, f: F) -> Self {
+        Self { target, f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for ContextMenu<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let Resp { id: target_id, l: target_l } = self.target;
+        if ui.hovered(target_id, *target_l) && ui.rclicked(target_id, *target_l) {
+            let p = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+            ui.mem_mut().user::<ContextMenuState>(&id)?.pt = Some(p);
+        }
+
+        let Some(p) = ui.mem_mut().user::<ContextMenuState>(&id)?.pt else {
+            return Ok(Resp { id, l: LclLayer::default() });
+        };
+        let mut open = true;
+        let popup_id = combine_ids(&[&self.lcl_id(ui), "popup"]);
+        let resp = Popup::new(&popup_id, p, &mut open, &mut self.f).ui(ui)?;
+        if !open || ui.io().menu_activated {
+            ui.mem_mut().user::<ContextMenuState>(&id)?.pt = None;
+        }
+        Ok(Resp { id, l: resp.l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&[&self.target.id, "context_menu"])
+    }
+}
@@ -1,5 +1,30 @@
+pub mod axis;
 pub mod button;
+pub mod canvas;
+pub mod chart;
+pub mod collapse;
+pub mod color_picker;
+pub mod combo;
 pub mod debug;
+pub mod heatmap;
+pub mod hex_view;
+pub mod image;
+pub mod image_viewer;
+pub mod inspector;
+pub mod json_view;
 pub mod label;
+pub mod list;
+pub mod log_console;
+pub mod menu;
+pub mod node_graph;
+pub mod plot;
+pub mod popup;
+pub mod progress;
+pub mod stream;
+pub mod table;
+pub mod tabs;
+pub mod text_edit;
+pub mod tooltip;
+pub mod tree;
 pub mod widget;
 pub mod window;
@@ -0,0 +1,455 @@
+use std::any::Any;
+
+use ahash::HashMap;
+use eyre::Result;
+use lyon::path::Path;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::NamedKey;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{PlotView, Range};
+use crate::visual::gui::widgets::canvas::CanvasView;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, LclRt, LclSz, WldPt, WldRt, lpt, lrt, lsz, lz, wpt, wrt};
+use crate::visual::vis::UserData;
+
+const NODE_W: f64 = 150.0;
+const HEADER_DP: f64 = 22.0;
+const PIN_ROW_DP: f64 = 20.0;
+const PIN_R: f64 = 5.0;
+const PAD_DP: f64 = 6.0;
+const ZOOM: Range = Range::new(0.2, 4.0);
+// Text is left out below this zoom, since it'd be unreadable.
+const TEXT_ZOOM: f64 = 0.4;
+
+// A named input or output of a node. |ty| is up to the caller, e.g. to only
+// allow wires between pins of the same type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub ty: String,
+}
+
+impl Pin {
+    #[must_use]
+    pub fn new(name: &str, ty: &str) -> Self {
+        Self { name: name.to_owned(), ty: ty.to_owned() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: String,
+    pub title: String,
+    pub inputs: Vec<Pin>,
+    pub outputs: Vec<Pin>,
+    // Where the node starts. Once moved, where it is moved to is remembered
+    // instead.
+    pub pos: WldPt,
+}
+
+impl Node {
+    #[must_use]
+    pub fn new(id: &str, title: &str, pos: WldPt) -> Self {
+        Self { id: id.to_owned(), title: title.to_owned(), inputs: vec![], outputs: vec![], pos }
+    }
+
+    #[must_use]
+    pub fn input(mut self, pin: Pin) -> Self {
+        self.inputs.push(pin);
+        self
+    }
+
+    #[must_use]
+    pub fn output(mut self, pin: Pin) -> Self {
+        self.outputs.push(pin);
+        self
+    }
+
+    // Rect of the node at |pos|.
+    fn rt(&self, pos: WldPt) -> WldRt {
+        let rows = self.inputs.len().max(self.outputs.len()) as f64;
+        wrt(pos.x, pos.y, NODE_W, HEADER_DP + rows * PIN_ROW_DP + PAD_DP)
+    }
+
+    // Centre of input or output pin |i| of the node at |pos|.
+    fn pin_pt(pos: WldPt, out: bool, i: usize) -> WldPt {
+        let x = if out { pos.x + NODE_W } else { pos.x };
+        wpt(x, pos.y + HEADER_DP + (i as f64 + 0.5) * PIN_ROW_DP)
+    }
+}
+
+// Connects output |from.1| of node |from.0| to input |to.1| of node |to.0|.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wire {
+    pub from: (String, usize),
+    pub to: (String, usize),
+}
+
+impl Wire {
+    #[must_use]
+    pub fn new(from: (&str, usize), to: (&str, usize)) -> Self {
+        Self { from: (from.0.to_owned(), from.1), to: (to.0.to_owned(), to.1) }
+    }
+}
+
+// A pin as the index of its node, whether it's an output, and its index.
+type PinRef = (usize, bool, usize);
+
+fn pin(nodes: &[Node], (n, out, i): PinRef) -> &Pin {
+    if out { &nodes[n].outputs[i] } else { &nodes[n].inputs[i] }
+}
+
+// Pins of |node| as whether each is an output, and its index.
+fn pins(node: &Node) -> impl Iterator<Item = (bool, usize)> {
+    (0..node.inputs.len()).map(|i| (false, i)).chain((0..node.outputs.len()).map(|i| (true, i)))
+}
+
+// Adds |w|, replacing any wire already going into the same input.
+fn connect(wires: &mut Vec<Wire>, w: Wire) {
+    wires.retain(|o| o.to != w.to);
+    wires.push(w);
+}
+
+// Removes the nodes with ids in |ids| and any wires to or from them.
+fn remove_nodes(nodes: &mut Vec<Node>, wires: &mut Vec<Wire>, ids: &[String]) {
+    nodes.retain(|n| !ids.contains(&n.id));
+    wires.retain(|w| !ids.contains(&w.from.0) && !ids.contains(&w.to.0));
+}
+
+// Horizontal S-curve from |a| to |b|, leaving |a| to the right and entering
+// |b| from the left.
+fn wire_path(a: LclPt, b: LclPt, s: f64) -> Path {
+    let d = ((b.x - a.x).abs() / 2.0).max(40.0 * s);
+    let mut p = Path::builder();
+    p.begin(a.into());
+    p.cubic_bezier_to(lpt(a.x + d, a.y).into(), lpt(b.x - d, b.y).into(), b.into());
+    p.end(false);
+    p.build()
+}
+
+// Whether |a| and |b| overlap.
+fn overlaps(a: LclRt, b: LclRt) -> bool {
+    let i = a.intersect(&b);
+    i.w > 0.0 && i.h > 0.0
+}
+
+// View of a graph and positions of nodes moved in it, by node id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GraphState {
+    view: Option<PlotView>,
+    node_pos: HashMap<String, WldPt>,
+}
+
+#[typetag::serde]
+impl UserData for GraphState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Editor for a graph of |nodes| joined by |wires|. Nodes are dragged around
+// by their body, and wires are made by dragging between an output and an
+// input which |compatible| allows. Dragging a wire off an input disconnects
+// it. Dragging the background pans, shift dragging selects with a box, and
+// the wheel zooms. Delete removes the selected nodes. Node positions and the
+// view are remembered per graph.
+#[derive(Debug)]
+pub struct NodeGraph<'a, F: FnMut(&Pin, &Pin) -> bool> {
+    id: String,
+    nodes: &'a mut Vec<Node>,
+    wires: &'a mut Vec<Wire>,
+    sel: &'a mut Vec<String>,
+    // Whether a wire can go from the first, an output, to the second, an
+    // input.
+    compatible: F,
+    sz: LclSz,
+}
+
+impl<'a, F: FnMut(&Pin, &Pin) -> bool> NodeGraph<'a, F> {
+    pub fn new(
+        id: &str,
+        nodes: &'a mut Vec<Node>,
+        wires: &'a mut Vec<Wire>,
+        sel: &'a mut Vec<String>,
+        compatible: F,
+    ) -> Self {
+        Self { id: id.to_owned(), nodes, wires, sel, compatible, sz: lsz(600, 400) }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    // Wire joining |a| and |b| if they're an output and an input of different
+    // nodes which can be connected.
+    fn wire_between(&mut self, a: PinRef, b: PinRef) -> Option<Wire> {
+        let (from, to) = match (a.1, b.1) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => return None,
+        };
+        if from.0 == to.0 || !(self.compatible)(pin(self.nodes, from), pin(self.nodes, to)) {
+            return None;
+        }
+        Some(Wire {
+            from: (self.nodes[from.0].id.clone(), from.2),
+            to: (self.nodes[to.0].id.clone(), to.2),
+        })
+    }
+
+    // Pin under |p| on the screen, if any.
+    fn pin_at(&self, cam: &CanvasView, pos: &[WldPt], p: LclPt) -> Option<PinRef> {
+        let hit = (PIN_R + 3.0) * cam.s.max(1.0);
+        let near = |q: LclPt| (q.x - p.x).hypot(q.y - p.y) <= hit;
+        self.nodes.iter().enumerate().rev().find_map(|(n, node)| {
+            pins(node)
+                .find(|&(out, i)| near(cam.lcl(Node::pin_pt(pos[n], out, i))))
+                .map(|(out, i)| (n, out, i))
+        })
+    }
+
+    fn draw_node(
+        &self,
+        ui: &mut Ui<'_>,
+        cam: &CanvasView,
+        n: usize,
+        pos: WldPt,
+        l: LclLayer,
+    ) -> Result<()> {
+        let node = &self.nodes[n];
+        let r = l.r;
+        let s = cam.s;
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.9));
+        ui.fill_rrt(r, 4.0 * s);
+        scope.col(ui.s.acc3_col.with_alpha(0.5));
+        ui.fill_rrt(lrt(r.x, r.y, r.w, HEADER_DP * s), 4.0 * s);
+        if self.sel.contains(&node.id) {
+            scope.col(ui.s.acc1_col).line_width(2.0);
+            ui.stroke_rrt(r, 4.0 * s);
+        }
+        for (out, i) in pins(node) {
+            let c = cam.lcl(Node::pin_pt(pos, out, i));
+            scope.col(ui.s.light_col);
+            ui.fill_circ(c, PIN_R * s);
+            if s < TEXT_ZOOM {
+                continue;
+            }
+            let (x, g) = if out {
+                (c.x - 2.0 * PIN_R * s, Grav::End)
+            } else {
+                (c.x + 2.0 * PIN_R * s, Grav::Begin)
+            };
+            scope.col(ui.s.light_col.with_alpha(0.8));
+            ui.text(
+                &Frag::new(&pin(self.nodes, (n, out, i)).name, ui.s.font_sz * s, lpt(x, c.y))
+                    .hgrav(g)
+                    .vgrav(Grav::Center),
+            )?;
+        }
+        if s >= TEXT_ZOOM {
+            scope.col(ui.s.light_col).clip(lrt(r.x, r.y, r.w, HEADER_DP * s));
+            let p = lpt(r.x + PAD_DP * s, r.y + HEADER_DP * s / 2.0);
+            ui.text(&Frag::new(&node.title, ui.s.font_sz * s, p).vgrav(Grav::Center))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&Pin, &Pin) -> bool> Widget for NodeGraph<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let io = ui.io();
+        let mouse = ui.info().gtf.inv().pt(io.mouse_pt);
+        let prev = ui.info().gtf.inv().pt(io.mouse_pt - io.mouse_delta.to_sz());
+        let press_pt = ui.info().gtf.inv().pt(io.mouse_pressed_pt);
+        let (just_pressed, just_captured) = (io.mouse_just_pressed, io.mouse_just_captured);
+        let (shift, wheel) = (io.mods.shift_key(), io.mouse_scroll.y);
+        let delete = io.kbd_captured.is_none()
+            && (io.key_pressed(NamedKey::Delete) || io.key_pressed(NamedKey::Backspace));
+        let released = io.mouse_just_released.then(|| io.mouse_captured.clone()).flatten();
+        let st = ui.mem_mut().user::<GraphState>(&id)?;
+        let mut cam = CanvasView::new(l.r, st.view);
+        let mut pos: Vec<WldPt> =
+            self.nodes.iter().map(|n| st.node_pos.get(&n.id).copied().unwrap_or(n.pos)).collect();
+        let delta = (mouse - prev).to_sz();
+
+        let scope = ui.push();
+        scope.clip(l.r);
+        let bg = LclLayer::new(l.r, l.z);
+        // Whether the graph has the mouse, over the background or a node.
+        let mut hovered = ui.hovered(&id, bg);
+        let bg_pressed = ui.pressed(&id, bg);
+
+        // Nodes and pins, topmost last. Later nodes are drawn over earlier
+        // ones, so they also get the mouse first.
+        let node_l = |n: usize, r: LclRt| LclLayer::new(r, l.z + lz(2 + 2 * n));
+        let mut dragging = false;
+        let mut wire_from = None;
+        let mut dropped = None;
+        for (n, node) in self.nodes.iter().enumerate() {
+            let nl = node_l(n, cam.lcl_rt(node.rt(pos[n])));
+            let node_id = combine_ids(&[&id, "node", &node.id]);
+            hovered |= ui.hovered(&node_id, nl);
+            if ui.pressed(&node_id, nl) {
+                if just_pressed && !self.sel.contains(&node.id) {
+                    if !shift {
+                        self.sel.clear();
+                    }
+                    self.sel.push(node.id.clone());
+                }
+                dragging = !just_captured;
+            }
+            for (out, i) in pins(node) {
+                let c = cam.lcl(Node::pin_pt(pos[n], out, i));
+                let hit = (PIN_R + 3.0) * cam.s.max(1.0);
+                let pl =
+                    LclLayer::new(lrt(c.x - hit, c.y - hit, 2.0 * hit, 2.0 * hit), nl.z + lz(1));
+                let pin_id =
+                    combine_ids(&[&node_id, if out { "out" } else { "in" }, &i.to_string()]);
+                hovered |= ui.hovered(&pin_id, pl);
+                if ui.pressed(&pin_id, pl) {
+                    wire_from = Some((n, out, i));
+                }
+                if released.as_deref() == Some(pin_id.as_str()) {
+                    dropped = Some((n, out, i));
+                }
+            }
+        }
+        if dragging {
+            for (n, node) in self.nodes.iter().enumerate() {
+                if self.sel.contains(&node.id) {
+                    pos[n] += cam.wld_sz(delta);
+                }
+            }
+        }
+
+        // Finish dragging a wire from a pin: connect it to the pin it was
+        // dropped on, or if it was dropped on nothing and came from an input,
+        // disconnect that input.
+        if let Some(a) = dropped {
+            match self.pin_at(&cam, &pos, mouse) {
+                Some(b) => {
+                    if let Some(w) = self.wire_between(a, b) {
+                        connect(self.wires, w);
+                    }
+                }
+                None if !a.1 => {
+                    let to = (self.nodes[a.0].id.clone(), a.2);
+                    self.wires.retain(|w| w.to != to);
+                }
+                None => {}
+            }
+        }
+
+        // Background: clicking clears the selection, dragging pans or with
+        // shift selects a box, and the wheel zooms.
+        let mut sel_box = None;
+        if bg_pressed {
+            if just_pressed && !shift {
+                self.sel.clear();
+            }
+            if shift {
+                let r =
+                    LclRt::ptsz(press_pt, LclSz::zero()).union(&LclRt::ptsz(mouse, LclSz::zero()));
+                self.sel.clear();
+                for (n, node) in self.nodes.iter().enumerate() {
+                    if overlaps(cam.lcl_rt(node.rt(pos[n])), r) {
+                        self.sel.push(node.id.clone());
+                    }
+                }
+                sel_box = Some(r);
+            } else if !just_captured {
+                cam.pan(delta);
+            }
+        }
+        if hovered && !wheel.is_zero() {
+            cam.zoom_at(mouse, wheel, ZOOM);
+        }
+        if hovered && delete {
+            let ids = std::mem::take(self.sel);
+            pos = self
+                .nodes
+                .iter()
+                .zip(pos)
+                .filter(|(n, _)| !ids.contains(&n.id))
+                .map(|(_, p)| p)
+                .collect();
+            remove_nodes(self.nodes, self.wires, &ids);
+            ui.mem_mut().user::<GraphState>(&id)?.node_pos.retain(|k, _| !ids.contains(k));
+        }
+        // Background, then wires under the nodes.
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rt(l.r);
+        scope.z(l.z + lz(1)).col(ui.s.light_col.with_alpha(0.7)).line_width(2.0);
+        let index = |id: &str| self.nodes.iter().position(|n| n.id == id);
+        for w in &*self.wires {
+            let (Some(a), Some(b)) = (index(&w.from.0), index(&w.to.0)) else { continue };
+            if w.from.1 >= self.nodes[a].outputs.len() || w.to.1 >= self.nodes[b].inputs.len() {
+                continue;
+            }
+            let pa = cam.lcl(Node::pin_pt(pos[a], true, w.from.1));
+            let pb = cam.lcl(Node::pin_pt(pos[b], false, w.to.1));
+            ui.stroke_path(wire_path(pa, pb, cam.s));
+        }
+        for (n, node) in self.nodes.iter().enumerate() {
+            let nl = node_l(n, cam.lcl_rt(node.rt(pos[n])));
+            self.draw_node(ui, &cam, n, pos[n], nl)?;
+        }
+
+        // The wire being dragged, over everything else.
+        scope.z(l.z + lz(2 + 2 * self.nodes.len())).col(ui.s.acc3_col);
+        if let Some((n, out, i)) = wire_from {
+            let p = cam.lcl(Node::pin_pt(pos[n], out, i));
+            let (a, b) = if out { (p, mouse) } else { (mouse, p) };
+            ui.stroke_path(wire_path(a, b, cam.s));
+        }
+        if let Some(r) = sel_box {
+            scope.col(ui.s.acc3_col.with_alpha(0.2));
+            ui.fill_rt(r);
+            scope.col(ui.s.acc3_col).line_width(1.0);
+            ui.stroke_rt(r);
+        }
+
+        let st = ui.mem_mut().user::<GraphState>(&id)?;
+        st.view = Some(cam.view());
+        for (node, p) in self.nodes.iter().zip(pos) {
+            if p != node.pos || st.node_pos.contains_key(&node.id) {
+                st.node_pos.insert(node.id.clone(), p);
+            }
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["node_graph", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit() {
+        let mut nodes = vec![
+            Node::new("a", "A", WldPt::zero()),
+            Node::new("b", "B", WldPt::zero()),
+            Node::new("c", "C", WldPt::zero()),
+        ];
+        let mut wires = vec![Wire::new(("a", 0), ("b", 0))];
+        connect(&mut wires, Wire::new(("c", 0), ("b", 0)));
+        connect(&mut wires, Wire::new(("a", 0), ("b", 1)));
+        assert_eq!(wires, [Wire::new(("c", 0), ("b", 0)), Wire::new(("a", 0), ("b", 1))]);
+        remove_nodes(&mut nodes, &mut wires, &["c".to_owned()]);
+        assert_eq!(nodes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(wires, [Wire::new(("a", 0), ("b", 1))]);
+    }
+}
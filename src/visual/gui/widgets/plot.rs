@@ -0,0 +1,232 @@
+use eyre::Result;
+use lyon::path::Path;
+
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{
+    PlotArea, PlotView, Range, Ticks, ViewState, draw_axes, draw_legend, fmt_tick, pan_zoom, ticks,
+};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{Col, LclPt, LclSz, lsz, lz};
+
+const POINT_DP: f64 = 2.5;
+const HOVER_DP: f64 = 16.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SeriesKind {
+    Line,
+    Scatter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series<'a> {
+    pub name: String,
+    pub pts: &'a [[f64; 2]],
+    pub kind: SeriesKind,
+    pub col: Option<Col>,
+}
+
+impl<'a> Series<'a> {
+    #[must_use]
+    pub fn line(name: &str, pts: &'a [[f64; 2]]) -> Self {
+        Self { name: name.to_owned(), pts, kind: SeriesKind::Line, col: None }
+    }
+
+    #[must_use]
+    pub fn scatter(name: &str, pts: &'a [[f64; 2]]) -> Self {
+        Self { kind: SeriesKind::Scatter, ..Self::line(name, pts) }
+    }
+
+    // Overrides the colour from the style's palette.
+    #[must_use]
+    pub fn col(self, col: Col) -> Self {
+        Self { col: Some(col), ..self }
+    }
+}
+
+// View fitting all the points of |series|.
+fn fit_view(series: &[Series<'_>]) -> PlotView {
+    let pts = || series.iter().flat_map(|s| s.pts.iter());
+    let x = Range::of(pts().map(|p| p[0])).unwrap_or(Range::new(0.0, 1.0));
+    let y = Range::of(pts().map(|p| p[1])).unwrap_or(Range::new(0.0, 1.0));
+    PlotView { x: x.pad(0.05), y: y.pad(0.05) }
+}
+
+// Series and index of the point nearest to |p| on screen, within |max_d|.
+fn nearest(a: &PlotArea, series: &[Series<'_>], p: LclPt, max_d: f64) -> Option<(usize, usize)> {
+    let mut best = None;
+    let mut best_d = max_d * max_d;
+    for (si, s) in series.iter().enumerate() {
+        for (i, v) in s.pts.iter().enumerate() {
+            let d = (a.to_scr(v[0], v[1]) - p).to_sz();
+            let d = d.w * d.w + d.h * d.h;
+            if d < best_d {
+                best_d = d;
+                best = Some((si, i));
+            }
+        }
+    }
+    best
+}
+
+// Draws |series| in |a|. Callers clip to the plot area.
+pub fn draw_series(ui: &mut Ui<'_>, a: &PlotArea, series: &[Series<'_>]) {
+    let scope = ui.push();
+    scope.z(a.l.z + lz(1));
+    for (si, s) in series.iter().enumerate() {
+        scope.col(s.col.unwrap_or_else(|| ui.s.series_col(si)));
+        match s.kind {
+            SeriesKind::Line => {
+                // Break the line at missing values.
+                let mut b = Path::builder();
+                let mut open = false;
+                for v in s.pts {
+                    let p = a.to_scr(v[0], v[1]);
+                    if !p.x.is_finite() || !p.y.is_finite() {
+                        if open {
+                            b.end(false);
+                        }
+                        open = false;
+                    } else if open {
+                        b.line_to(p.into());
+                    } else {
+                        b.begin(p.into());
+                        open = true;
+                    }
+                }
+                if open {
+                    b.end(false);
+                }
+                ui.stroke_path(b.build());
+            }
+            SeriesKind::Scatter => {
+                for v in s.pts {
+                    let p = a.to_scr(v[0], v[1]);
+                    if a.l.r.contains(p) {
+                        ui.fill_circ(p, POINT_DP);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Marks the point of |series| nearest the mouse and shows its value.
+pub fn hover(ui: &mut Ui<'_>, a: &PlotArea, series: &[Series<'_>]) -> Result<()> {
+    let mouse = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+    let Some((si, i)) = nearest(a, series, mouse, HOVER_DP) else {
+        return Ok(());
+    };
+    let s = &series[si];
+    let [x, y] = s.pts[i];
+    let p = a.to_scr(x, y);
+    let scope = ui.push();
+    scope.z(a.l.z + lz(3)).col(s.col.unwrap_or_else(|| ui.s.series_col(si)));
+    ui.stroke_circ(p, POINT_DP * 2.0);
+
+    // Show enough precision to tell apart values a pixel apart.
+    let (_, x_step) = ticks(a.view.x, a.l.r.w.max(1.0) as usize);
+    let (_, y_step) = ticks(a.view.y, a.l.r.h.max(1.0) as usize);
+    let text = format!("{}: ({}, {})", s.name, fmt_tick(x, x_step), fmt_tick(y, y_step));
+    ui.tooltip(&text)?;
+    Ok(())
+}
+
+// Plots line and scatter series. Wheel zooms, dragging pans and right clicking
+// resets the view to fit the data.
+#[derive(Debug)]
+pub struct Plot<'a> {
+    id: String,
+    series: Vec<Series<'a>>,
+    sz: LclSz,
+    legend: bool,
+}
+
+impl<'a> Plot<'a> {
+    #[must_use]
+    pub fn new(id: &str) -> Self {
+        Self { id: id.to_owned(), series: Vec::new(), sz: lsz(400, 250), legend: true }
+    }
+
+    #[must_use]
+    pub fn series(mut self, s: Series<'a>) -> Self {
+        self.series.push(s);
+        self
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    #[must_use]
+    pub fn legend(self, legend: bool) -> Self {
+        Self { legend, ..self }
+    }
+}
+
+impl Widget for Plot<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let view = ui.mem_mut().user::<ViewState>(&id)?.view;
+        let mut a = PlotArea::new(l, view.unwrap_or_else(|| fit_view(&self.series)));
+        let hovered = pan_zoom(ui, &id, &mut a);
+        if ui.rclicked(&id, a.l) {
+            a.view = fit_view(&self.series);
+        }
+        ui.mem_mut().user::<ViewState>(&id)?.view = Some(a.view);
+
+        draw_axes(ui, &a, Ticks::Values, Ticks::Values)?;
+        {
+            let scope = ui.push();
+            scope.clip(a.l.r);
+            draw_series(ui, &a, &self.series);
+        }
+        if self.legend {
+            let entries: Vec<_> = self
+                .series
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (s.name.as_str(), s.col.unwrap_or_else(|| ui.s.series_col(i))))
+                .collect();
+            draw_legend(ui, a.l, &entries)?;
+        }
+        if hovered && !ui.io().is_mouse_pressed {
+            hover(ui, &a, &self.series)?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["plot", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual::gui::layer::LclLayer;
+    use crate::visual::types::{lpt, lrt};
+
+    #[test]
+    fn test_fit_view() {
+        let pts = [[0.0, 0.0], [10.0, 20.0], [f64::NAN, 5.0]];
+        let v = fit_view(&[Series::line("a", &pts)]);
+        assert_eq!(v.x, Range::new(-0.5, 10.5));
+        assert_eq!(v.y, Range::new(-1.0, 21.0));
+        assert_eq!(fit_view(&[]).x, Range::new(0.0, 1.0).pad(0.05));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let view = PlotView { x: Range::new(0.0, 10.0), y: Range::new(0.0, 10.0) };
+        let a = PlotArea { l: LclLayer::new(lrt(0, 0, 100, 100), lz(0)), view };
+        let s1 = [[1.0, 1.0], [5.0, 5.0]];
+        let s2 = [[5.5, 5.0]];
+        let series = [Series::line("a", &s1), Series::scatter("b", &s2)];
+        assert_eq!(nearest(&a, &series, lpt(54, 50), 10.0), Some((1, 0)));
+        assert_eq!(nearest(&a, &series, lpt(11, 89), 10.0), Some((0, 0)));
+        assert_eq!(nearest(&a, &series, lpt(90, 10), 10.0), None);
+    }
+}
@@ -0,0 +1,165 @@
+use std::any::Any;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::NamedKey;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{GblRt, GblZ, LclPt, LclSz, POPUP_Z, lz};
+use crate::visual::vis::UserData;
+
+const POPUP_Z_OFF: GblZ = GblZ::new(1000);
+
+// Z-order for a popup opened from |ui|. Nested popups stack above their parent.
+#[must_use]
+pub fn popup_z(ui: &Ui<'_>) -> GblZ {
+    ui.info().gtf.z(lz(0)).max(POPUP_Z.coerce()) + POPUP_Z_OFF
+}
+
+// Moves |p| so that a rect of size |sz| placed there stays on the screen.
+#[must_use]
+pub fn clamp_to_scr(ui: &Ui<'_>, p: LclPt, sz: LclSz) -> LclPt {
+    let gtf = ui.info().gtf;
+    let scr = ui.io().scr_sz;
+    let mut gp = gtf.pt(p);
+    gp.x = gp.x.min(scr.w - sz.w).max(0.0);
+    gp.y = gp.y.min(scr.h - sz.h).max(0.0);
+    gtf.inv().pt(gp)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PopupState {
+    #[serde(skip)]
+    sz: LclSz,
+}
+
+#[typetag::serde]
+impl UserData for PopupState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Size of the popup with id |id| last frame. Popups are placed before they're
+// laid out, so they go by this.
+pub fn popup_sz<'a>(ui: &'a mut Ui<'_>, id: &str) -> Result<&'a mut LclSz> {
+    Ok(&mut ui.mem_mut().user::<PopupState>(id)?.sz)
+}
+
+pub fn draw_popup_bg(ui: &mut Ui<'_>, l: LclLayer) {
+    let scope = ui.push();
+    scope.z(l.z - lz(1)).col(ui.s.dark_col.with_alpha(0.95));
+    ui.fill_rrt(l.r, 4.0);
+    scope.col(ui.s.acc3_col.with_alpha(0.95));
+    ui.stroke_rrt(l.r, 4.0);
+}
+
+#[derive(Debug)]
+pub struct Popup<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    id: String,
+    p: LclPt,
+    open: &'a mut bool,
+    f: F,
+}
+
+impl<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> Popup<'a, F> {
+    pub fn new(id: &str, p: LclPt, open: &'a mut bool, f: F) -> Self {
+        Self { id: id.to_owned(), p, open, f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for Popup<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        if !*self.open {
+            return Ok(Resp { id, l: LclLayer::default() });
+        }
+
+        let sz = *popup_sz(ui, &id)?;
+        let p = clamp_to_scr(ui, self.p, sz);
+        let z = popup_z(ui);
+        let l = ui.overlay(p, z, &Hint::new(), &id, &mut self.f)?;
+        if l.r.sz() != sz {
+            // Placed using last frame's size, so redo it with the new one.
+            ui.io_mut().request_redraw();
+        }
+        *popup_sz(ui, &id)? = l.r.sz();
+        draw_popup_bg(ui, l);
+
+        // Take the mouse over the whole popup so nothing beneath reacts.
+        ui.hovered(&id, l);
+        ui.pressed(&id, l);
+
+        // Anything inside this popup, including nested popups, has an id
+        // prefixed by this popup's id.
+        let io = ui.io();
+        let inside = io.has_mouse.as_deref().is_some_and(|m| m.starts_with(&id));
+        let pressed = io.mouse_just_pressed || io.rmouse_just_pressed;
+        if (pressed && !inside) || io.key_pressed(NamedKey::Escape) {
+            *self.open = false;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        self.id.clone()
+    }
+}
+
+#[derive(Debug)]
+pub struct Modal<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> {
+    id: String,
+    open: &'a mut bool,
+    f: F,
+}
+
+impl<'a, F: FnMut(&mut Ui<'_>) -> Result<()>> Modal<'a, F> {
+    pub fn new(id: &str, open: &'a mut bool, f: F) -> Self {
+        Self { id: id.to_owned(), open, f }
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for Modal<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        if !*self.open {
+            return Ok(Resp { id, l: LclLayer::default() });
+        }
+
+        // Center on the screen using last frame's size.
+        let gtf = ui.info().gtf;
+        let sz = *popup_sz(ui, &id)?;
+        let scr = GblRt::from_sz(ui.io().scr_sz);
+        let p = gtf.inv().pt(scr.center()) - (sz / 2.0);
+        let z = popup_z(ui);
+        let l = ui.overlay(p, z, &Hint::new(), &id, &mut self.f)?;
+        if l.r.sz() != sz {
+            ui.io_mut().request_redraw();
+        }
+        *popup_sz(ui, &id)? = l.r.sz();
+        draw_popup_bg(ui, l);
+        ui.hovered(&id, l);
+        ui.pressed(&id, l);
+
+        // Dimmed backdrop beneath the dialog which swallows all other clicks.
+        let backdrop_id = combine_ids(&[&id, "backdrop"]);
+        let backdrop = LclLayer::new(gtf.inv().rt(scr), l.z - lz(2));
+        ui.hovered(&backdrop_id, backdrop);
+        ui.pressed(&backdrop_id, backdrop);
+        if ui.clicked(&backdrop_id, backdrop) || ui.io().key_pressed(NamedKey::Escape) {
+            *self.open = false;
+        }
+        let scope = ui.push();
+        scope.z(backdrop.z).col(ui.s.dark_col.with_alpha(0.5));
+        ui.fill_rt(backdrop.r);
+
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        self.id.clone()
+    }
+}
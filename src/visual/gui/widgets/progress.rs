@@ -0,0 +1,212 @@
+use std::f64::consts::TAU;
+
+use eyre::Result;
+use lyon::path::Path;
+use num_traits::Zero;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, LclSz, lpt, lrt, lsz};
+
+// Seconds for the indeterminate stripe to cross the bar, and for a spinner
+// to turn once.
+const STRIPE_PERIOD: f64 = 1.5;
+const SPIN_PERIOD: f64 = 1.0;
+// Width of the indeterminate stripe as a fraction of the bar.
+const STRIPE_W: f64 = 0.3;
+const SPINNER_SEGMENTS: usize = 24;
+
+// Seconds since the app started, for animations.
+fn anim_time(ui: &Ui<'_>) -> f64 {
+    (ui.io().begin_frame_time - ui.io().start_time).as_secs_f64()
+}
+
+// Start and end of the indeterminate stripe, as fractions of the bar, at
+// |t| seconds. It slides in from the left and out of the right.
+fn stripe(t: f64) -> (f64, f64) {
+    let x = (t / STRIPE_PERIOD).fract() * (1.0 + STRIPE_W) - STRIPE_W;
+    (x.max(0.0), (x + STRIPE_W).min(1.0))
+}
+
+// Three quarters of a circle, turned by how far through a spin |t| seconds
+// is.
+fn spinner_arc(c: LclPt, radius: f64, t: f64) -> Path {
+    let start = (t / SPIN_PERIOD).fract() * TAU;
+    let pt = |i: usize| {
+        let a = start + 0.75 * TAU * i as f64 / SPINNER_SEGMENTS as f64;
+        lpt(c.x + a.sin() * radius, c.y - a.cos() * radius)
+    };
+    let mut b = Path::builder();
+    b.begin(pt(0).into());
+    for i in 1..=SPINNER_SEGMENTS {
+        b.line_to(pt(i).into());
+    }
+    b.end(false);
+    b.build()
+}
+
+// Shows how far through something is, or a moving stripe if that's unknown.
+#[derive(Debug)]
+pub struct ProgressBar {
+    frac: Option<f64>,
+    text: Option<String>,
+    sz: LclSz,
+}
+
+impl ProgressBar {
+    // Filled to |frac|, from 0 to 1.
+    #[must_use]
+    pub fn new(frac: f64) -> Self {
+        Self { frac: Some(frac.clamp(0.0, 1.0)), text: None, sz: lsz(200, 18) }
+    }
+
+    #[must_use]
+    pub fn indeterminate() -> Self {
+        Self { frac: None, ..Self::new(0.0) }
+    }
+
+    // Text shown on the bar, e.g. "3 of 10 files".
+    #[must_use]
+    pub fn text(self, text: &str) -> Self {
+        Self { text: Some(text.to_owned()), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+}
+
+impl Widget for ProgressBar {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let r = l.r;
+        let (x0, x1) = if let Some(frac) = self.frac {
+            (0.0, frac)
+        } else {
+            ui.animate(l);
+            stripe(anim_time(ui))
+        };
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.light_col.with_alpha(0.2));
+        ui.fill_rrt(r, 4.0);
+        if x1 > x0 {
+            scope.col(ui.s.acc3_col).clip(r);
+            ui.fill_rrt(lrt(r.x + x0 * r.w, r.y, (x1 - x0) * r.w, r.h), 4.0);
+        }
+        if let Some(text) = &self.text {
+            scope.col(ui.s.light_col);
+            let f = Frag::new(text, ui.s.font_sz, r.center());
+            ui.text(&f.hgrav(Grav::Center).vgrav(Grav::Center))?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["progress_bar", self.text.as_deref().unwrap_or_default()])
+    }
+}
+
+// Spinning arc showing that something is busy.
+#[derive(Debug)]
+pub struct Spinner {
+    radius: f64,
+}
+
+impl Spinner {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { radius: 8.0 }
+    }
+
+    #[must_use]
+    pub fn radius(self, radius: f64) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Spinner {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let d = 2.0 * self.radius + 4.0;
+        let l = ui.child_layer(&Hint::make_exact(lsz(d, d)));
+        ui.animate(l);
+
+        let c = l.r.center();
+        let arc = spinner_arc(c, self.radius, anim_time(ui));
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.light_col.with_alpha(0.2)).line_width(2.0);
+        ui.stroke_circ(c, self.radius);
+        scope.col(ui.s.acc3_col);
+        ui.stroke_path(arc);
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        "spinner".to_owned()
+    }
+}
+
+// Spinner followed by |text|, e.g. "Loading...".
+#[derive(Debug)]
+pub struct Busy {
+    text: String,
+}
+
+impl Busy {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_owned() }
+    }
+}
+
+impl Widget for Busy {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let f = Frag::new(&self.text, ui.s.font_sz, LclPt::zero());
+        let tsz = ui.text_sz(&f)?;
+        let radius = (tsz.h / 2.0).max(6.0);
+        let d = 2.0 * radius + 4.0;
+        let l = ui.child_layer(&Hint::make_exact(lsz(d + ui.s.pad.w + tsz.w, d.max(tsz.h))));
+        let spin_l = LclLayer::new(lrt(l.r.x, l.r.y, d, d), l.z);
+        ui.animate(spin_l);
+        let arc = spinner_arc(spin_l.r.center(), radius, anim_time(ui));
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.acc3_col).line_width(2.0);
+        ui.stroke_path(arc);
+        scope.col(ui.s.light_col);
+        ui.text(&f.pt(lpt(l.r.x + d + ui.s.pad.w, l.r.center().y)).vgrav(Grav::Center))?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["busy", &self.text])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_stripe() {
+        assert_eq!(stripe(0.0), (0.0, 0.0));
+        let (x0, x1) = stripe(STRIPE_PERIOD / 2.0);
+        assert!((x0 - 0.35).abs() < 1e-9 && (x1 - 0.65).abs() < 1e-9);
+        assert_eq!(stripe(STRIPE_PERIOD * 0.99).1, 1.0);
+        assert_eq!(stripe(STRIPE_PERIOD * 1.5), stripe(STRIPE_PERIOD / 2.0));
+    }
+}
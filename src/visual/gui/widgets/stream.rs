@@ -0,0 +1,401 @@
+use std::collections::VecDeque;
+
+use eyre::Result;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::axis::{
+    PlotArea, PlotView, Range, Ticks, ViewState, draw_axes, draw_legend, pan_zoom,
+};
+use crate::visual::gui::widgets::plot::{Series, draw_series, hover};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{Col, LclSz, lpt, lrt, lsz, lz};
+
+const SCRUB_DP: f64 = 14.0;
+const BTN_DP: f64 = 18.0;
+const BTN_PAD_DP: f64 = 6.0;
+
+// Fixed capacity buffer of samples in order of x, dropping the oldest once
+// full.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ring {
+    pts: VecDeque<[f64; 2]>,
+    cap: usize,
+}
+
+impl Ring {
+    #[must_use]
+    pub fn new(cap: usize) -> Self {
+        Self { pts: VecDeque::with_capacity(cap), cap: cap.max(1) }
+    }
+
+    // Adds a sample. |x| must not be less than the previous sample's.
+    pub fn push(&mut self, x: f64, y: f64) {
+        if self.pts.len() == self.cap {
+            self.pts.pop_front();
+        }
+        self.pts.push_back([x, y]);
+    }
+
+    pub fn clear(&mut self) {
+        self.pts.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pts.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pts.is_empty()
+    }
+
+    // Range of x covered by the samples, if any.
+    #[must_use]
+    pub fn x_range(&self) -> Option<Range> {
+        Some(Range::new(self.pts.front()?[0], self.pts.back()?[0]))
+    }
+
+    // Samples with x in |r|, plus one either side so lines reach the edges.
+    fn window(&self, r: Range) -> Vec<[f64; 2]> {
+        let lo = self.pts.partition_point(|p| p[0] < r.min).saturating_sub(1);
+        let hi = (self.pts.partition_point(|p| p[0] <= r.max) + 1).min(self.pts.len());
+        self.pts.range(lo..hi.max(lo)).copied().collect()
+    }
+}
+
+// A named series of samples streamed in over time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamSeries {
+    pub name: String,
+    pub buf: Ring,
+    pub col: Option<Col>,
+}
+
+impl StreamSeries {
+    // Keeps the last |cap| samples.
+    #[must_use]
+    pub fn new(name: &str, cap: usize) -> Self {
+        Self { name: name.to_owned(), buf: Ring::new(cap), col: None }
+    }
+
+    #[must_use]
+    pub fn col(self, col: Col) -> Self {
+        Self { col: Some(col), ..self }
+    }
+
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.buf.push(x, y);
+    }
+}
+
+// How to reduce the samples in view to about one or two per pixel column
+// before building paths.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Decimate {
+    // The minimum and maximum of each column, which keeps every spike.
+    MinMax,
+    // Largest triangle three buckets, which keeps the visual shape.
+    Lttb,
+}
+
+// Reduces |pts| to the minimum and maximum in each of |cols| columns across
+// |r|, in their original order. Non-finite samples are kept to break lines.
+fn min_max(pts: &[[f64; 2]], r: Range, cols: usize) -> Vec<[f64; 2]> {
+    if pts.len() <= 2 * cols || r.span() <= 0.0 {
+        return pts.to_vec();
+    }
+    let mut out = Vec::with_capacity(2 * cols + 2);
+    // Column and the indices of the minimum and maximum in it.
+    let mut cur: Option<(i64, usize, usize)> = None;
+    let flush = |out: &mut Vec<[f64; 2]>, cur: Option<(i64, usize, usize)>| {
+        if let Some((_, lo, hi)) = cur {
+            out.push(pts[lo.min(hi)]);
+            if lo != hi {
+                out.push(pts[lo.max(hi)]);
+            }
+        }
+    };
+    for (i, p) in pts.iter().enumerate() {
+        if !p[1].is_finite() {
+            flush(&mut out, cur.take());
+            out.push(*p);
+            continue;
+        }
+        let col = ((p[0] - r.min) / r.span() * cols as f64).floor() as i64;
+        match cur {
+            Some((c, lo, hi)) if c == col => {
+                let lo = if p[1] < pts[lo][1] { i } else { lo };
+                let hi = if p[1] > pts[hi][1] { i } else { hi };
+                cur = Some((c, lo, hi));
+            }
+            _ => {
+                flush(&mut out, cur);
+                cur = Some((col, i, i));
+            }
+        }
+    }
+    flush(&mut out, cur);
+    out
+}
+
+// Reduces |pts| to |n| points with largest triangle three buckets. The first
+// and last points are always kept.
+fn lttb(pts: &[[f64; 2]], n: usize) -> Vec<[f64; 2]> {
+    let len = pts.len();
+    if n >= len || n < 3 {
+        return pts.to_vec();
+    }
+    let every = (len - 2) as f64 / (n - 2) as f64;
+    let bucket = |i: usize| ((i as f64 * every) as usize + 1).min(len - 1);
+    let mut out = Vec::with_capacity(n);
+    out.push(pts[0]);
+    let mut a = 0;
+    for i in 0..n - 2 {
+        // Average of the next bucket, or the last point for the last bucket.
+        let next = &pts[bucket(i + 1)..bucket(i + 2).max(bucket(i + 1) + 1).min(len)];
+        let k = next.len() as f64;
+        let avg = next.iter().fold([0.0, 0.0], |s, p| [s[0] + p[0] / k, s[1] + p[1] / k]);
+
+        let [ax, ay] = pts[a];
+        let mut best = (bucket(i), f64::NEG_INFINITY);
+        for (j, p) in pts.iter().enumerate().take(bucket(i + 1)).skip(bucket(i)) {
+            let area = ((ax - avg[0]) * (p[1] - ay) - (ax - p[0]) * (avg[1] - ay)).abs();
+            if area > best.1 {
+                best = (j, area);
+            }
+        }
+        out.push(pts[best.0]);
+        a = best.0;
+    }
+    out.push(pts[len - 1]);
+    out
+}
+
+// Plots live samples, scrolling to show the last |window| of x. Panning,
+// zooming or dragging the history bar below pauses it to look back through
+// history, and the button in the corner or right clicking resumes.
+#[derive(Debug)]
+pub struct StreamPlot<'a> {
+    id: String,
+    series: &'a [StreamSeries],
+    window: f64,
+    decimate: Decimate,
+    y: Option<Range>,
+    sz: LclSz,
+}
+
+impl<'a> StreamPlot<'a> {
+    #[must_use]
+    pub fn new(id: &str, series: &'a [StreamSeries]) -> Self {
+        Self {
+            id: id.to_owned(),
+            series,
+            window: 10.0,
+            decimate: Decimate::MinMax,
+            y: None,
+            sz: lsz(400, 250),
+        }
+    }
+
+    // Span of x shown while live.
+    #[must_use]
+    pub fn window(self, window: f64) -> Self {
+        Self { window, ..self }
+    }
+
+    #[must_use]
+    pub fn decimate(self, decimate: Decimate) -> Self {
+        Self { decimate, ..self }
+    }
+
+    // Fixes the y range rather than fitting it to the samples in view.
+    #[must_use]
+    pub fn y_range(self, y: Range) -> Self {
+        Self { y: Some(y), ..self }
+    }
+
+    #[must_use]
+    pub fn size(self, sz: LclSz) -> Self {
+        Self { sz, ..self }
+    }
+
+    // View of |x| with y fitted to the samples in it, unless fixed.
+    fn live_view(&self, x: Range) -> PlotView {
+        let ys = self.series.iter().flat_map(|s| s.buf.window(x)).map(|p| p[1]);
+        let y = self.y.or_else(|| Range::of(ys).map(|r| r.pad(0.05)));
+        PlotView { x, y: y.unwrap_or(Range::new(0.0, 1.0)) }
+    }
+
+    // Toggle between live and paused. Returns whether it was clicked.
+    fn pause_btn(ui: &mut Ui<'_>, id: &str, a: &PlotArea, paused: bool) -> bool {
+        let r = lrt(a.l.r.x + BTN_PAD_DP, a.l.r.y + BTN_PAD_DP, BTN_DP, BTN_DP);
+        let l = LclLayer::new(r, a.l.z + lz(2));
+        let btn_id = combine_ids(&[id, "pause"]);
+        let hovered = ui.hovered(&btn_id, l);
+        ui.pressed(&btn_id, l);
+        let clicked = ui.clicked(&btn_id, l);
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.dark_col.with_alpha(if hovered { 0.9 } else { 0.6 }));
+        ui.fill_rrt(r, 4.0);
+        scope.col(ui.s.light_col);
+        let (c, d) = (r.center(), BTN_DP / 4.0);
+        if paused {
+            ui.fill_poly(vec![lpt(c.x - d, c.y - d), lpt(c.x + d, c.y), lpt(c.x - d, c.y + d)]);
+        } else {
+            ui.fill_rt(lrt(c.x - d, c.y - d, d * 0.7, 2.0 * d));
+            ui.fill_rt(lrt(c.x + d * 0.3, c.y - d, d * 0.7, 2.0 * d));
+        }
+        clicked
+    }
+
+    // Bar showing the view within the whole history. Dragging it moves the
+    // view. Returns whether it was dragged.
+    fn scrub(ui: &mut Ui<'_>, id: &str, l: LclLayer, hist: Range, a: &mut PlotArea) -> bool {
+        let scrub_id = combine_ids(&[id, "scrub"]);
+        ui.hovered(&scrub_id, l);
+        let pressed = ui.pressed(&scrub_id, l);
+        let r = l.r;
+        if pressed && hist.span() > 0.0 {
+            let mouse = ui.info().gtf.inv().pt(ui.io().mouse_pt);
+            let x = hist.unmap(mouse.x.clamp(r.x, r.r()), r.x, r.r());
+            a.view.x = a.view.x.shift(x - f64::midpoint(a.view.x.min, a.view.x.max));
+        }
+
+        let scope = ui.push();
+        scope.z(l.z).col(ui.s.light_col.with_alpha(0.1));
+        ui.fill_rrt(r, 3.0);
+        if hist.span() > 0.0 {
+            let x0 = hist.map(a.view.x.min, r.x, r.r()).clamp(r.x, r.r());
+            let x1 = hist.map(a.view.x.max, r.x, r.r()).clamp(r.x, r.r());
+            scope.col(ui.s.acc1_col.with_alpha(if pressed { 0.8 } else { 0.5 }));
+            ui.fill_rrt(lrt(x0, r.y, (x1 - x0).max(2.0), r.h), 3.0);
+        }
+        pressed
+    }
+}
+
+impl Widget for StreamPlot<'_> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child_layer(&Hint::make_exact(self.sz));
+        let plot_l = LclLayer::new(lrt(l.r.x, l.r.y, l.r.w, l.r.h - SCRUB_DP), l.z);
+        let hist = self.series.iter().filter_map(|s| s.buf.x_range()).reduce(|a, b| a.union(&b));
+        let latest = hist.map_or(0.0, |r| r.max);
+        let live_x = Range::new(latest - self.window, latest);
+
+        // A stored view means paused.
+        let stored = ui.mem_mut().user::<ViewState>(&id)?.view;
+        let live = self.live_view(live_x);
+        let view = stored.unwrap_or(live);
+        let mut a = PlotArea::new(plot_l, view);
+        let hovered = pan_zoom(ui, &id, &mut a);
+        let mut paused = stored.is_some() || a.view != view;
+        if Self::pause_btn(ui, &id, &a, paused) {
+            paused = !paused;
+        }
+        if ui.rclicked(&id, a.l) {
+            paused = false;
+        }
+        let strip = lrt(a.l.r.x, l.r.b() - SCRUB_DP + 4.0, a.l.r.w, SCRUB_DP - 4.0);
+        if Self::scrub(ui, &id, LclLayer::new(strip, l.z), hist.unwrap_or(live_x), &mut a) {
+            paused = true;
+        }
+
+        if !paused {
+            // Samples arrive without input, so keep drawing while live.
+            a.view = live;
+            ui.animate(l);
+        }
+        ui.mem_mut().user::<ViewState>(&id)?.view = paused.then_some(a.view);
+        let windows: Vec<_> = self.series.iter().map(|s| s.buf.window(a.view.x)).collect();
+
+        // Bound the cost of building paths by the width, not the sample count.
+        let cols = a.l.r.w.max(1.0) as usize;
+        let pts: Vec<_> = windows
+            .iter()
+            .map(|w| match self.decimate {
+                Decimate::MinMax => min_max(w, a.view.x, cols),
+                Decimate::Lttb => lttb(w, 2 * cols),
+            })
+            .collect();
+        let series: Vec<_> = self
+            .series
+            .iter()
+            .zip(&pts)
+            .map(|(s, p)| Series { col: s.col, ..Series::line(&s.name, p) })
+            .collect();
+
+        draw_axes(ui, &a, Ticks::Values, Ticks::Values)?;
+        {
+            let scope = ui.push();
+            scope.clip(a.l.r);
+            draw_series(ui, &a, &series);
+        }
+        let entries: Vec<_> = series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name.as_str(), s.col.unwrap_or_else(|| ui.s.series_col(i))))
+            .collect();
+        draw_legend(ui, a.l, &entries)?;
+        if hovered && !ui.io().is_mouse_pressed {
+            hover(ui, &a, &series)?;
+        }
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["stream_plot", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring() {
+        let mut r = Ring::new(3);
+        assert_eq!(r.x_range(), None);
+        for i in 0..5 {
+            r.push(f64::from(i), 0.0);
+        }
+        assert_eq!(r.len(), 3);
+        assert_eq!(r.x_range(), Some(Range::new(2.0, 4.0)));
+        assert_eq!(r.window(Range::new(3.0, 3.5)), [[2.0, 0.0], [3.0, 0.0], [4.0, 0.0]]);
+        assert_eq!(r.window(Range::new(10.0, 11.0)), [[4.0, 0.0]]);
+        assert_eq!(r.window(Range::new(-5.0, -4.0)), [[2.0, 0.0]]);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_min_max() {
+        let pts: Vec<_> = (0..100).map(|i| [f64::from(i), f64::from(i % 7)]).collect();
+        let out = min_max(&pts, Range::new(0.0, 100.0), 10);
+        assert!(out.len() <= 20);
+        assert!(out.windows(2).all(|w| w[0][0] <= w[1][0]));
+        assert_eq!(out.iter().filter(|p| p[1] == 6.0).count(), 10);
+        assert_eq!(min_max(&pts[..5], Range::new(0.0, 100.0), 10), pts[..5]);
+
+        let gap = [[0.0, 1.0], [0.1, f64::NAN], [0.2, 2.0], [0.3, 3.0], [0.4, 4.0]];
+        let out = min_max(&gap, Range::new(0.0, 1.0), 1);
+        assert!(out[1][1].is_nan());
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_lttb() {
+        let mut pts: Vec<_> = (0..1000).map(|i| [f64::from(i), 0.0]).collect();
+        pts[500][1] = 100.0;
+        let out = lttb(&pts, 50);
+        assert_eq!(out.len(), 50);
+        assert_eq!(out[0], pts[0]);
+        assert_eq!(out[49], pts[999]);
+        assert!(out.contains(&pts[500]));
+        assert_eq!(lttb(&pts[..10], 50).len(), 10);
+    }
+}
@@ -0,0 +1,311 @@
+use std::any::Any;
+use std::cmp::Ordering;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::layouts::horiz_layout::HorizLayout;
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, LclRt, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const CELL_PAD_DP: f64 = 6.0;
+const HANDLE_DP: f64 = 6.0;
+const MIN_COL_DP: f64 = 24.0;
+const SCROLL_DP: f64 = 30.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub w: f64,
+}
+
+impl Column {
+    #[must_use]
+    pub fn new(name: &str, w: f64) -> Self {
+        Self { name: name.to_owned(), w }
+    }
+}
+
+type Sort = Option<(usize, bool)>;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TableState {
+    // Column widths once resized, and the sort column and direction.
+    col_ws: Vec<f64>,
+    sort: Sort,
+    #[serde(skip)]
+    scroll: f64,
+    // Display order of the rows and the sort, number of rows and version it
+    // is for.
+    #[serde(skip)]
+    order: Option<(Sort, usize, u64, Vec<usize>)>,
+}
+
+#[typetag::serde]
+impl UserData for TableState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Compares cells numerically if both are numbers, otherwise as text. Numbers
+// go before text so a column mixing both is still totally ordered.
+fn cmp_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+// Order to display |n| rows in, sorted by column |col| if given. The sort is
+// stable so equal rows keep their original order.
+fn row_order(n: usize, sort: Sort, cell: &mut impl FnMut(usize, usize) -> String) -> Vec<usize> {
+    let Some((col, asc)) = sort else {
+        return (0..n).collect();
+    };
+    let mut keys: Vec<_> = (0..n).map(|r| (cell(r, col), r)).collect();
+    keys.sort_by(|(a, _), (b, _)| if asc { cmp_cells(a, b) } else { cmp_cells(b, a) });
+    keys.into_iter().map(|(_, r)| r).collect()
+}
+
+// A scrollable table of |rows| rows, with the text of each cell given by
+// |cell|. Clicking a header sorts by that column, and dragging the edge of a
+// header resizes it. The sorted order is kept until the sort, the number of
+// rows or the version changes.
+#[derive(Debug)]
+pub struct Table<'a, F: FnMut(usize, usize) -> String> {
+    id: String,
+    cols: Vec<Column>,
+    rows: usize,
+    cell: F,
+    sel: Option<&'a mut Option<usize>>,
+    h: f64,
+    version: u64,
+}
+
+impl<'a, F: FnMut(usize, usize) -> String> Table<'a, F> {
+    pub fn new(id: &str, cols: Vec<Column>, rows: usize, cell: F) -> Self {
+        Self { id: id.to_owned(), cols, rows, cell, sel: None, h: 300.0, version: 0 }
+    }
+
+    // Allows selecting a row by clicking it. |sel| is the row index given to
+    // |cell|, regardless of sorting.
+    #[must_use]
+    pub fn sel(self, sel: &'a mut Option<usize>) -> Self {
+        Self { sel: Some(sel), ..self }
+    }
+
+    // Maximum height of the rows, excluding the header.
+    #[must_use]
+    pub fn height(self, h: f64) -> Self {
+        Self { h, ..self }
+    }
+
+    // Version of the cells, which should change whenever their text does so
+    // the rows are sorted again.
+    #[must_use]
+    pub fn version(self, version: u64) -> Self {
+        Self { version, ..self }
+    }
+
+    // Draws |text| in |r|, clipped to it.
+    fn cell_text(ui: &mut Ui<'_>, r: LclRt, text: &str) -> Result<()> {
+        let scope = ui.push();
+        scope.clip(r);
+        let f = Frag::new(text, ui.s.font_sz, lpt(r.x + CELL_PAD_DP, r.center().y));
+        ui.text(&f.vgrav(Grav::Center))
+    }
+
+    fn header(&self, ui: &mut Ui<'_>, id: &str, ws: &mut [f64], row_h: f64) -> Result<()> {
+        let mut sort = ui.mem_mut().user::<TableState>(id)?.sort;
+        let mut resized = false;
+        for (i, c) in self.cols.iter().enumerate() {
+            let l = ui.child_layer(&Hint::make_exact(lsz(ws[i], row_h)));
+            let col_id = combine_ids(&[id, "col", &i.to_string()]);
+            let handle_id = combine_ids(&[&col_id, "handle"]);
+
+            // Resize handle straddling the right edge, above the header.
+            let handle =
+                LclLayer::new(lrt(l.r.r() - HANDLE_DP / 2.0, l.r.y, HANDLE_DP, l.r.h), l.z + lz(1));
+            let handle_hovered = ui.hovered(&handle_id, handle);
+            if ui.pressed(&handle_id, handle) {
+                ws[i] = (ws[i] + ui.io().mouse_delta.x).max(MIN_COL_DP);
+                resized = true;
+            }
+
+            let hovered = ui.hovered(&col_id, l);
+            ui.pressed(&col_id, l);
+            if ui.clicked(&col_id, l) {
+                sort = match sort {
+                    Some((s, asc)) if s == i => Some((i, !asc)),
+                    _ => Some((i, true)),
+                };
+            }
+
+            let scope = ui.push();
+            scope.col(ui.s.acc3_col.with_alpha(if hovered { 0.6 } else { 0.4 }));
+            ui.fill_rt(l.r);
+            scope.col(ui.s.light_col.with_alpha(if handle_hovered { 0.8 } else { 0.3 }));
+            ui.stroke_line(l.r.tr(), l.r.br());
+            scope.col(ui.s.light_col);
+            Self::cell_text(ui, lrt(l.r.x, l.r.y, l.r.w - row_h / 2.0, l.r.h), &c.name)?;
+            if let Some((s, asc)) = sort
+                && s == i
+            {
+                let (x, cy) = (l.r.r() - row_h / 2.0, l.r.center().y);
+                let dy = if asc { -3.0 } else { 3.0 };
+                ui.fill_poly(vec![lpt(x - 4.0, cy - dy), lpt(x + 4.0, cy - dy), lpt(x, cy + dy)]);
+            }
+        }
+        let st = ui.mem_mut().user::<TableState>(id)?;
+        st.sort = sort;
+        if resized {
+            st.col_ws = ws.to_vec();
+        }
+        Ok(())
+    }
+
+    fn body(&mut self, ui: &mut Ui<'_>, id: &str, ws: &[f64], row_h: f64) -> Result<()> {
+        let st = ui.mem_mut().user::<TableState>(id)?;
+        let sort = st.sort.filter(|(c, _)| *c < self.cols.len());
+        let order = match st.order.take() {
+            Some((s, n, v, order)) if s == sort && n == self.rows && v == self.version => order,
+            _ => row_order(self.rows, sort, &mut self.cell),
+        };
+        let w: f64 = ws.iter().sum();
+        let total_h = self.rows as f64 * row_h;
+        let body = ui.child_layer(&Hint::make_exact(lsz(w, total_h.min(self.h))));
+
+        let body_id = combine_ids(&[id, "body"]);
+        let wheel = ui.scrolled(&body_id, body).y;
+        let st = ui.mem_mut().user::<TableState>(id)?;
+        st.scroll = (st.scroll - wheel * SCROLL_DP).clamp(0.0, (total_h - body.r.h).max(0.0));
+        let scroll = st.scroll;
+
+        // Only lay out the visible rows.
+        let first = (scroll / row_h).floor() as usize;
+        let last = (((scroll + body.r.h) / row_h).ceil() as usize).min(self.rows);
+
+        let scope = ui.push();
+        scope.clip(body.r);
+        for (i, &row) in order.iter().enumerate().take(last).skip(first) {
+            let r = lrt(body.r.x, body.r.y + i as f64 * row_h - scroll, w, row_h);
+            let l = LclLayer::new(r, body.z);
+            let row_id = combine_ids(&[id, "row", &row.to_string()]);
+            let hovered = ui.hovered(&row_id, l);
+            if let Some(sel) = self.sel.as_deref_mut() {
+                ui.pressed(&row_id, l);
+                if ui.clicked(&row_id, l) {
+                    *sel = Some(row);
+                }
+            }
+
+            let selected = self.sel.as_deref().is_some_and(|s| *s == Some(row));
+            let col = if selected {
+                Some(ui.s.acc3_col.with_alpha(0.5))
+            } else if hovered && self.sel.is_some() {
+                Some(ui.s.light_col.with_alpha(0.15))
+            } else if i % 2 == 1 {
+                Some(ui.s.light_col.with_alpha(0.05))
+            } else {
+                None
+            };
+            if let Some(col) = col {
+                scope.col(col);
+                ui.fill_rt(r);
+            }
+
+            scope.col(ui.s.light_col);
+            let mut x = r.x;
+            for (c, &cw) in ws.iter().enumerate() {
+                let text = (self.cell)(row, c);
+                Self::cell_text(ui, lrt(x, r.y, cw, row_h), &text)?;
+                x += cw;
+            }
+        }
+        ui.mem_mut().user::<TableState>(id)?.order = Some((sort, self.rows, self.version, order));
+        Ok(())
+    }
+}
+
+impl<F: FnMut(usize, usize) -> String> Widget for Table<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let row_h = ui.text_sz(&Frag::new(" ", ui.s.font_sz, LclPt::zero()))?.h + CELL_PAD_DP;
+
+        // Column widths are persisted once resized.
+        let mut ws = ui.mem_mut().user::<TableState>(&id)?.col_ws.clone();
+        ws.resize(self.cols.len(), 0.0);
+        for (w, c) in ws.iter_mut().zip(&self.cols) {
+            if w.is_zero() {
+                *w = c.w;
+            }
+        }
+
+        let header_id = combine_ids(&[&id, "header"]);
+        let l = ui.child(
+            &Hint::new(),
+            &id,
+            |info| Layout::new(VertLayout::new(info)),
+            |ui| {
+                // The header is outside the scrolled area so it stays put.
+                ui.child(
+                    &Hint::new(),
+                    &header_id,
+                    |info| Layout::new(HorizLayout::new(info)),
+                    |ui| self.header(ui, &id, &mut ws, row_h),
+                )?;
+                self.body(ui, &id, &ws, row_h)
+            },
+        )?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["table", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmp_cells() {
+        assert_eq!(cmp_cells("9", "10"), Ordering::Less);
+        assert_eq!(cmp_cells(" 2.5", "-1"), Ordering::Greater);
+        assert_eq!(cmp_cells("b", "a"), Ordering::Greater);
+        assert_eq!(cmp_cells("10", "a"), Ordering::Less);
+        assert_eq!(cmp_cells("1a", "2"), Ordering::Greater);
+        assert_eq!(cmp_cells("1a", "10"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_row_order() {
+        let data = [["b", "2"], ["a", "10"], ["c", "2"]];
+        let mut cell = |r: usize, c: usize| data[r][c].to_owned();
+        assert_eq!(row_order(3, None, &mut cell), [0, 1, 2]);
+        assert_eq!(row_order(3, Some((0, true)), &mut cell), [1, 0, 2]);
+        assert_eq!(row_order(3, Some((0, false)), &mut cell), [2, 0, 1]);
+        assert_eq!(row_order(3, Some((1, true)), &mut cell), [0, 2, 1]);
+        assert_eq!(row_order(3, Some((1, false)), &mut cell), [1, 0, 2]);
+    }
+
+    #[test]
+    fn test_row_order_mixed() {
+        let data = ["2", "b", "10", "1a", "1"];
+        let mut cell = |r: usize, _: usize| data[r].to_owned();
+        assert_eq!(row_order(5, Some((0, true)), &mut cell), [4, 0, 2, 3, 1]);
+        assert_eq!(row_order(5, Some((0, false)), &mut cell), [1, 3, 2, 0, 4]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::any::Any;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, LclSz, lpt, lrt, lsz, lz};
+use crate::visual::vis::UserData;
+
+const HEADER_PAD: LclSz = LclSz::new(10.0, 3.0);
+const CLOSE_DP: f64 = 12.0;
+const SCROLL_DP: f64 = 30.0;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TabsState {
+    // Name of the selected tab.
+    sel: Option<String>,
+    #[serde(skip)]
+    scroll: f64,
+}
+
+#[typetag::serde]
+impl UserData for TabsState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// A row of tab headers showing the body of the selected tab only. Headers can
+// be dragged to reorder |names|, and optionally closed which removes them.
+#[derive(Debug)]
+pub struct Tabs<'a, F: FnMut(&mut Ui<'_>, &str) -> Result<()>> {
+    id: String,
+    names: &'a mut Vec<String>,
+    closable: bool,
+    f: F,
+}
+
+impl<'a, F: FnMut(&mut Ui<'_>, &str) -> Result<()>> Tabs<'a, F> {
+    pub fn new(id: &str, names: &'a mut Vec<String>, f: F) -> Self {
+        Self { id: id.to_owned(), names, closable: false, f }
+    }
+
+    #[must_use]
+    pub fn closable(self, closable: bool) -> Self {
+        Self { closable, ..self }
+    }
+
+    // Lays out the header strip and returns the selected tab.
+    fn headers(&mut self, ui: &mut Ui<'_>) -> Result<Option<String>> {
+        let id = ui.id().to_owned();
+        let close_w = if self.closable { CLOSE_DP + HEADER_PAD.w / 2.0 } else { 0.0 };
+        let mut frags = Vec::with_capacity(self.names.len());
+        let mut ws = Vec::with_capacity(self.names.len());
+        let mut h: f64 = 0.0;
+        for tab in self.names.iter() {
+            let f = Frag::new(tab, ui.s.font_sz, LclPt::zero());
+            let sz = ui.text_sz(&f)?;
+            ws.push(sz.w + 2.0 * HEADER_PAD.w + close_w);
+            h = h.max(sz.h + 2.0 * HEADER_PAD.h);
+            frags.push(f);
+        }
+
+        // Scroll the headers if they don't fit in the available width.
+        let total: f64 = ws.iter().sum();
+        let avail = ui.info().hint.max.map_or(total, |m| m.w);
+        let strip = ui.child_layer(&Hint::make_exact(lsz(total.min(avail), h)));
+        let scroll = ui.scrolled(&combine_ids(&[&id, "strip"]), strip);
+        let st = ui.mem_mut().user::<TabsState>(&id)?;
+        st.scroll = (st.scroll + (scroll.x + scroll.y) * SCROLL_DP)
+            .clamp(0.0, (total - strip.r.w).max(0.0));
+        let scroll_x = st.scroll;
+        let mut sel = st
+            .sel
+            .clone()
+            .filter(|s| self.names.contains(s))
+            .or_else(|| self.names.first().cloned());
+
+        let scope = ui.push();
+        scope.clip(strip.r);
+        let mut swap = None;
+        let mut closed = None;
+        let mut x = strip.r.x - scroll_x;
+        for (i, (tab, f)) in self.names.iter().zip(frags).enumerate() {
+            let r = lrt(x, strip.r.y, ws[i], h);
+            let l = LclLayer::new(r, strip.z);
+            x += ws[i];
+
+            // Select on press, then swap with a neighbour once dragged past
+            // its center. Tabs with the same name are told apart by which one
+            // they are, which dragging doesn't change.
+            let nth = self.names[..i].iter().filter(|n| *n == tab).count();
+            let tab_id = combine_ids(&[&id, "tab", tab, &nth.to_string()]);
+            let hovered = ui.hovered(&tab_id, l);
+            if ui.pressed(&tab_id, l) {
+                if ui.io().mouse_just_captured {
+                    sel = Some(tab.clone());
+                }
+                let mx = ui.info().gtf.inv().pt(ui.io().mouse_pt).x;
+                if i > 0 && mx < r.x - ws[i - 1] / 2.0 {
+                    swap = Some((i, i - 1));
+                } else if i + 1 < ws.len() && mx > r.r() + ws[i + 1] / 2.0 {
+                    swap = Some((i, i + 1));
+                }
+            }
+
+            let col = if sel.as_deref() == Some(tab.as_str()) {
+                ui.s.acc3_col.with_alpha(0.8)
+            } else if hovered {
+                ui.s.light_col.with_alpha(0.3)
+            } else {
+                ui.s.light_col.with_alpha(0.15)
+            };
+            scope.col(col);
+            ui.fill_rrt(r.inset_xy(1.0, 0.0), 4.0);
+            ui.text(&f.pt(lpt(r.x + HEADER_PAD.w, r.center().y)).vgrav(Grav::Center))?;
+
+            if self.closable {
+                let c = lrt(
+                    r.r() - HEADER_PAD.w - CLOSE_DP,
+                    r.center().y - CLOSE_DP / 2.0,
+                    CLOSE_DP,
+                    CLOSE_DP,
+                );
+                let cl = LclLayer::new(c, strip.z + lz(1));
+                let close_id = combine_ids(&[&tab_id, "close"]);
+                let alpha = if ui.hovered(&close_id, cl) { 1.0 } else { 0.5 };
+                ui.pressed(&close_id, cl);
+                if ui.clicked(&close_id, cl) {
+                    closed = Some(i);
+                }
+                scope.col(ui.s.light_col.with_alpha(alpha));
+                let c = c.inset_xy(3.0, 3.0);
+                ui.stroke_line(c.tl(), c.br());
+                ui.stroke_line(c.tr(), c.bl());
+            }
+        }
+
+        if let Some((a, b)) = swap {
+            self.names.swap(a, b);
+        }
+        if let Some(i) = closed {
+            let tab = self.names.remove(i);
+            if sel.as_ref() == Some(&tab) {
+                sel = self.names.get(i.min(self.names.len().saturating_sub(1))).cloned();
+            }
+        }
+        ui.mem_mut().user::<TabsState>(&id)?.sel.clone_from(&sel);
+        Ok(sel)
+    }
+}
+
+impl<F: FnMut(&mut Ui<'_>, &str) -> Result<()>> Widget for Tabs<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let l = ui.child(
+            &Hint::new(),
+            &id,
+            |info| Layout::new(VertLayout::new(info)),
+            |ui| {
+                if let Some(sel) = self.headers(ui)? {
+                    (self.f)(ui, &sel)?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        self.id.clone()
+    }
+}
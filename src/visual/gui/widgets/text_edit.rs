@@ -0,0 +1,120 @@
+use std::any::Any;
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::NamedKey;
+
+use crate::visual::gui::layer::LclLayer;
+use crate::visual::gui::layouts::hint::Grav;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::types::lpt;
+use crate::visual::vis::UserData;
+
+const PAD_DP: f64 = 4.0;
+
+// What happened to a text field this frame.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct EditState {
+    pub focused: bool,
+    pub hovered: bool,
+    pub changed: bool,
+    // Enter was pressed, or the field was clicked away from.
+    pub committed: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct EditBuf {
+    #[serde(skip)]
+    text: String,
+}
+
+#[typetag::serde]
+impl UserData for EditBuf {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Text being edited in the field with id |id|, kept between frames.
+pub fn edit_buf<'a>(ui: &'a mut Ui<'_>, id: &str) -> Result<&'a mut String> {
+    Ok(&mut ui.mem_mut().user::<EditBuf>(id)?.text)
+}
+
+// Handles focus and typing for a text field with id |id| in |l|, editing
+// |text|. Clicking focuses it, and Enter, Escape or clicking elsewhere
+// unfocuses it.
+pub fn edit_text(ui: &mut Ui<'_>, id: &str, l: LclLayer, text: &mut String) -> EditState {
+    let hovered = ui.hovered(id, l);
+    ui.pressed(id, l);
+    if ui.clicked(id, l) {
+        ui.focus(id);
+    }
+    let mut st = EditState { hovered, ..EditState::default() };
+    if !ui.focused(id) {
+        return st;
+    }
+    let io = ui.io();
+    let (enter, esc) = (io.key_pressed(NamedKey::Enter), io.key_pressed(NamedKey::Escape));
+    let away = io.mouse_just_pressed && !hovered;
+    let typed = io.typed();
+    if io.key_pressed(NamedKey::Backspace) {
+        st.changed |= text.pop().is_some();
+    }
+    st.changed |= !typed.is_empty();
+    text.push_str(&typed);
+    st.committed = enter || away;
+    st.cancelled = esc;
+    if st.committed || st.cancelled {
+        ui.unfocus(id);
+    }
+    st.focused = ui.focused(id);
+    st
+}
+
+// Draws a text field in |l| showing |text|, or |hint| in a dim colour if it
+// is empty and not being edited.
+pub fn draw_text_field(
+    ui: &mut Ui<'_>,
+    l: LclLayer,
+    st: EditState,
+    text: &str,
+    hint: &str,
+) -> Result<()> {
+    let scope = ui.push();
+    let alpha = if st.focused {
+        0.3
+    } else if st.hovered {
+        0.25
+    } else {
+        0.15
+    };
+    scope.z(l.z).col(ui.s.light_col.with_alpha(alpha)).clip(l.r);
+    ui.fill_rrt(l.r, 3.0);
+    let cy = l.r.center().y;
+    if text.is_empty() && !st.focused {
+        scope.col(ui.s.light_col.with_alpha(0.4));
+        ui.text(&Frag::new(hint, ui.s.font_sz, lpt(l.r.x + PAD_DP, cy)).vgrav(Grav::Center))?;
+        return Ok(());
+    }
+    let shown = if st.focused { format!("{text}|") } else { text.to_owned() };
+    let f = Frag::new(&shown, ui.s.font_sz, lpt(l.r.x + PAD_DP, cy)).vgrav(Grav::Center);
+    // Keep the end in view while typing past the width of the field.
+    let f = if ui.text_sz(&f)?.w > l.r.w - 2.0 * PAD_DP {
+        f.pt(lpt(l.r.r() - PAD_DP, cy)).hgrav(Grav::End)
+    } else {
+        f
+    };
+    scope.col(ui.s.light_col);
+    ui.text(&f)
+}
+
+// Filter text field in |l|, which keeps its text in memory under |id|.
+pub fn filter_field(ui: &mut Ui<'_>, id: &str, l: LclLayer, hint: &str) -> Result<String> {
+    let mut text = std::mem::take(edit_buf(ui, id)?);
+    let st = edit_text(ui, id, l, &mut text);
+    draw_text_field(ui, l, st, &text, hint)?;
+    edit_buf(ui, id)?.clone_from(&text);
+    Ok(text)
+}
@@ -0,0 +1,45 @@
+use eyre::Result;
+
+use crate::visual::gui::layouts::hint::Hint;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::popup::{clamp_to_scr, draw_popup_bg, popup_sz, popup_z};
+use crate::visual::gui::widgets::widget::{Resp, Widget};
+use crate::visual::types::lsz;
+
+#[derive(Debug)]
+pub struct Tooltip {
+    text: String,
+}
+
+impl Tooltip {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_owned() }
+    }
+}
+
+impl Widget for Tooltip {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        // Sits below and to the right of the cursor, so it doesn't take the
+        // mouse from the widget it describes.
+        let p = ui.info().gtf.inv().pt(ui.io().mouse_pt) + lsz(12, 16);
+        let sz = *popup_sz(ui, &id)?;
+        let p = clamp_to_scr(ui, p, sz);
+        let z = popup_z(ui);
+        let l = ui.overlay(p, z, &Hint::new(), &id, |ui| {
+            ui.label(&self.text)?;
+            Ok(())
+        })?;
+        if l.r.sz() != sz {
+            ui.io_mut().request_redraw();
+        }
+        *popup_sz(ui, &id)? = l.r.sz();
+        draw_popup_bg(ui, l);
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        "tooltip".to_owned()
+    }
+}
@@ -0,0 +1,294 @@
+use std::any::Any;
+
+use eyre::Result;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::NamedKey;
+
+use crate::visual::gui::layouts::hint::{Grav, Hint};
+use crate::visual::gui::layouts::layout::Layout;
+use crate::visual::gui::layouts::vert_layout::VertLayout;
+use crate::visual::gui::text::Frag;
+use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::collapse::{INDENT_DP, ROW_PAD_DP, disclosure, is_open, set_open};
+use crate::visual::gui::widgets::widget::{Resp, Widget, combine_ids};
+use crate::visual::types::{LclPt, lpt, lsz};
+use crate::visual::vis::UserData;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    pub id: String,
+    pub label: String,
+    pub leaf: bool,
+}
+
+impl TreeNode {
+    #[must_use]
+    pub fn new(id: &str, label: &str) -> Self {
+        Self { id: id.to_owned(), label: label.to_owned(), leaf: false }
+    }
+
+    #[must_use]
+    pub fn leaf(id: &str, label: &str) -> Self {
+        Self { leaf: true, ..Self::new(id, label) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    node: TreeNode,
+    depth: usize,
+    parent: Option<usize>,
+    open: bool,
+}
+
+// Flattens the visible part of the tree in display order. Children are only
+// requested for open nodes.
+fn flatten(
+    roots: &[TreeNode],
+    children: &mut impl FnMut(&str) -> Vec<TreeNode>,
+    mut open: impl FnMut(&TreeNode) -> Result<bool>,
+) -> Result<Vec<Row>> {
+    let mut rows: Vec<Row> = Vec::new();
+    let mut stack: Vec<_> = roots.iter().rev().map(|n| (n.clone(), 0, None)).collect();
+    while let Some((node, depth, parent)) = stack.pop() {
+        let open = !node.leaf && open(&node)?;
+        if open {
+            let i = rows.len();
+            stack.extend(children(&node.id).into_iter().rev().map(|c| (c, depth + 1, Some(i))));
+        }
+        rows.push(Row { node, depth, parent, open });
+    }
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TreeState {
+    // Id of the node with the keyboard cursor.
+    cursor: Option<String>,
+}
+
+#[typetag::serde]
+impl UserData for TreeState {
+    fn get_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Ids of the rows between |a| and |b| inclusive.
+fn range_sel(rows: &[Row], a: usize, b: usize) -> Vec<String> {
+    rows[a.min(b)..=a.max(b)].iter().map(|r| r.node.id.clone()).collect()
+}
+
+// Browses a hierarchy starting at |roots|. |children| is called to list the
+// children of each expanded node, every frame it is visible. Selected node
+// ids are kept in |sel|.
+#[derive(Debug)]
+pub struct TreeView<'a, F: FnMut(&str) -> Vec<TreeNode>> {
+    id: String,
+    roots: Vec<TreeNode>,
+    children: F,
+    sel: &'a mut Vec<String>,
+    multi: bool,
+}
+
+impl<'a, F: FnMut(&str) -> Vec<TreeNode>> TreeView<'a, F> {
+    pub fn new(id: &str, roots: Vec<TreeNode>, sel: &'a mut Vec<String>, children: F) -> Self {
+        Self { id: id.to_owned(), roots, children, sel, multi: false }
+    }
+
+    // Allows selecting several nodes with ctrl and shift click.
+    #[must_use]
+    pub fn multi(self, multi: bool) -> Self {
+        Self { multi, ..self }
+    }
+
+    fn select(&mut self, rows: &[Row], cursor: Option<usize>, i: usize, ui: &Ui<'_>) {
+        let mods = ui.io().mods;
+        let id = &rows[i].node.id;
+        if self.multi
+            && mods.shift_key()
+            && let Some(c) = cursor
+        {
+            *self.sel = range_sel(rows, c, i);
+        } else if self.multi && mods.control_key() {
+            if let Some(p) = self.sel.iter().position(|s| s == id) {
+                self.sel.remove(p);
+            } else {
+                self.sel.push(id.clone());
+            }
+        } else {
+            *self.sel = vec![id.clone()];
+        }
+    }
+
+    // Moves the cursor with the arrow keys, expanding and collapsing nodes
+    // with right and left. Returns the new cursor.
+    fn navigate(
+        &mut self,
+        ui: &mut Ui<'_>,
+        id: &str,
+        rows: &[Row],
+        cursor: usize,
+    ) -> Result<usize> {
+        let key = |k| ui.io().key_pressed(k);
+        let (up, down, right, left, enter) = (
+            key(NamedKey::ArrowUp),
+            key(NamedKey::ArrowDown),
+            key(NamedKey::ArrowRight),
+            key(NamedKey::ArrowLeft),
+            key(NamedKey::Enter),
+        );
+        let row = &rows[cursor];
+        let node_id = combine_ids(&[id, "node", &row.node.id]);
+        let mut next = cursor;
+        if up {
+            next = cursor.saturating_sub(1);
+        } else if down || (right && row.open) {
+            next = (cursor + 1).min(rows.len() - 1);
+        } else if right && !row.node.leaf {
+            set_open(ui, &node_id, true)?;
+        } else if left && row.open {
+            set_open(ui, &node_id, false)?;
+        } else if left && let Some(p) = row.parent {
+            next = p;
+        } else if enter && !row.node.leaf {
+            set_open(ui, &node_id, !row.open)?;
+        }
+        if next != cursor {
+            self.select(rows, Some(cursor), next, ui);
+        }
+        Ok(next)
+    }
+}
+
+impl<F: FnMut(&str) -> Vec<TreeNode>> Widget for TreeView<'_, F> {
+    fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp> {
+        let id = ui.wid(self);
+        let rows = flatten(&self.roots, &mut self.children, |n| {
+            is_open(ui, &combine_ids(&[&id, "node", &n.id]), false)
+        })?;
+
+        let mut frags = Vec::with_capacity(rows.len());
+        let mut w: f64 = 0.0;
+        let mut row_h: f64 = 0.0;
+        for row in &rows {
+            let f = Frag::new(&row.node.label, ui.s.font_sz, LclPt::zero());
+            let sz = ui.text_sz(&f)?;
+            let indent = (row.depth + 1) as f64 * INDENT_DP;
+            w = w.max(indent + sz.w + ROW_PAD_DP);
+            row_h = row_h.max(sz.h + ROW_PAD_DP);
+            frags.push(f);
+        }
+
+        let cursor_id = ui.mem_mut().user::<TreeState>(&id)?.cursor.clone();
+        let mut next = cursor_id.and_then(|c| rows.iter().position(|r| r.node.id == c));
+        let l = ui.child(
+            &Hint::new(),
+            &id,
+            |info| Layout::new(VertLayout::new(info)),
+            |ui| {
+                for (i, (row, f)) in rows.iter().zip(&frags).enumerate() {
+                    let l = ui.child_layer(&Hint::make_exact(lsz(w, row_h)));
+                    let r = l.r;
+                    let x = r.x + row.depth as f64 * INDENT_DP;
+                    let cy = r.center().y;
+
+                    // Clicking the triangle toggles, clicking the rest selects.
+                    let row_id = combine_ids(&[&id, "node", &row.node.id]);
+                    let hovered = ui.hovered(&row_id, l);
+                    ui.pressed(&row_id, l);
+                    if ui.clicked(&row_id, l) {
+                        ui.focus(&id);
+                        let mx = ui.info().gtf.inv().pt(ui.io().mouse_pt).x;
+                        if !row.node.leaf && mx < x + INDENT_DP {
+                            set_open(ui, &row_id, !row.open)?;
+                        } else {
+                            self.select(&rows, next, i, ui);
+                            next = Some(i);
+                        }
+                    }
+
+                    let selected = self.sel.contains(&row.node.id);
+                    if selected || hovered {
+                        let scope = ui.push();
+                        let alpha = if selected { 0.5 } else { 0.2 };
+                        scope.col(ui.s.acc3_col.with_alpha(alpha));
+                        ui.fill_rrt(r, 4.0);
+                    }
+                    if next == Some(i) && ui.focused(&id) {
+                        let scope = ui.push();
+                        scope.col(ui.s.acc3_col);
+                        ui.stroke_rrt(r, 4.0);
+                    }
+
+                    // Indentation guides, one per ancestor.
+                    {
+                        let scope = ui.push();
+                        scope.col(ui.s.light_col.with_alpha(0.2));
+                        for d in 0..row.depth {
+                            let gx = r.x + d as f64 * INDENT_DP + INDENT_DP / 2.0;
+                            ui.stroke_line(lpt(gx, r.y), lpt(gx, r.b()));
+                        }
+                    }
+
+                    if !row.node.leaf {
+                        disclosure(ui, lpt(x + INDENT_DP / 2.0, cy), row.open);
+                    }
+                    ui.text(&f.clone().pt(lpt(x + INDENT_DP, cy)).vgrav(Grav::Center))?;
+                }
+                Ok(())
+            },
+        )?;
+
+        if ui.focused(&id) {
+            if let Some(c) = next {
+                next = Some(self.navigate(ui, &id, &rows, c)?);
+            } else if !rows.is_empty() && ui.io().key_pressed(NamedKey::ArrowDown) {
+                self.select(&rows, None, 0, ui);
+                next = Some(0);
+            }
+        }
+        ui.mem_mut().user::<TreeState>(&id)?.cursor = next.map(|i| rows[i].node.id.clone());
+        Ok(Resp { id, l })
+    }
+
+    fn lcl_id(&self, _: &Ui<'_>) -> String {
+        combine_ids(&["tree", &self.id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn children(id: &str) -> Vec<TreeNode> {
+        match id {
+            "a" => vec![TreeNode::new("a/b", "b"), TreeNode::leaf("a/c", "c")],
+            "a/b" => vec![TreeNode::leaf("a/b/d", "d")],
+            _ => panic!("children of {id} requested"),
+        }
+    }
+
+    fn ids(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(|r| r.node.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_flatten() {
+        let roots = vec![TreeNode::new("a", "a"), TreeNode::leaf("e", "e")];
+        let rows = flatten(&roots, &mut children, |_| Ok(false)).unwrap();
+        assert_eq!(ids(&rows), ["a", "e"]);
+
+        let rows = flatten(&roots, &mut children, |n| Ok(n.id == "a")).unwrap();
+        assert_eq!(ids(&rows), ["a", "a/b", "a/c", "e"]);
+        assert_eq!(rows[1].parent, Some(0));
+        assert_eq!(rows[3].parent, None);
+
+        let rows = flatten(&roots, &mut children, |_| Ok(true)).unwrap();
+        assert_eq!(ids(&rows), ["a", "a/b", "a/b/d", "a/c", "e"]);
+        assert_eq!(rows[2].depth, 2);
+        assert_eq!(rows[2].parent, Some(1));
+        assert_eq!(range_sel(&rows, 3, 1), ["a/b", "a/b/d", "a/c"]);
+    }
+}
@@ -2,11 +2,41 @@ use eyre::Result;
 
 use crate::visual::gui::layer::LclLayer;
 use crate::visual::gui::ui::Ui;
+use crate::visual::gui::widgets::menu::ContextMenu;
 
+#[derive(Debug, Clone)]
 pub struct Resp {
+    pub id: String,
     pub l: LclLayer,
 }
 
+impl Resp {
+    // Shows |text| in a tooltip once the mouse has rested on this widget for
+    // the style's tooltip delay.
+    pub fn on_hover_text(&self, ui: &mut Ui<'_>, text: &str) -> Result<()> {
+        if !ui.hovered(&self.id, self.l) {
+            return Ok(());
+        }
+        let io = ui.io();
+        let shown_at = io.hover_since + ui.s.tooltip_delay;
+        if io.begin_frame_time < shown_at {
+            ui.io_mut().request_redraw_at(shown_at);
+        } else if !io.is_mouse_pressed {
+            ui.tooltip(text)?;
+        }
+        Ok(())
+    }
+
+    // Opens a menu at the mouse when this widget is right clicked.
+    pub fn context_menu(
+        &self,
+        ui: &mut Ui<'_>,
+        f: impl FnMut(&mut Ui<'_>) -> Result<()>,
+    ) -> Result<Resp> {
+        ContextMenu::new(self, f).ui(ui)
+    }
+}
+
 pub trait Widget {
     fn ui(&mut self, ui: &mut Ui<'_>) -> Result<Resp>;
     fn lcl_id(&self, ui: &Ui<'_>) -> String;
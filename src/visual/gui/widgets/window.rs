@@ -44,7 +44,7 @@ impl<F: FnMut(&mut Ui<'_>) -> Result<()>> Widget for Window<F> {
         scope.col(ui.s.acc3_col.with_alpha(0.95));
         ui.stroke_rrt(title_r, 4.0);
 
-        Ok(Resp { l })
+        Ok(Resp { id, l })
     }
 
     fn lcl_id(&self, _: &Ui<'_>) -> String {
@@ -2,6 +2,7 @@ use std::time::Instant;
 
 use num_traits::Zero;
 use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
 use winit::window::Window;
 
 use crate::visual::types::{GblPt, GblSz, GblZ, Pt, Pt2D, pt};
@@ -16,6 +17,8 @@ pub struct Io {
     pub kbd_captured: Option<String>,
     pub has_mouse: Option<String>,
     pub has_kbd: Option<String>,
+    pub mods: ModifiersState,
+    pub keys_pressed: Vec<Key>,
 
     // Mouse:
     pub mouse_pt: GblPt,
@@ -24,17 +27,29 @@ pub struct Io {
     pub mouse_captured: Option<String>,
     pub is_mouse_pressed: bool,
     pub mouse_pressed_pt: GblPt,
+    pub mouse_just_pressed: bool,
     pub mouse_just_released: bool,
     pub mouse_just_captured: bool,
+    pub is_rmouse_pressed: bool,
+    pub rmouse_just_pressed: bool,
+    pub rmouse_just_released: bool,
     pub mouse_scroll: Pt,
+    pub hover_since: Instant,
+    // Set when a menu item is activated, so open menus know to close.
+    pub menu_activated: bool,
     mouse_req: Option<(GblZ, String)>,
     mouse_capture_req: Option<(GblZ, String)>,
 
     // Frames:
+    // When the app started, as a base for animations.
+    pub start_time: Instant,
     pub begin_frame_time: Instant,
     pub prev_begin_frame_time: Instant,
     pub prev_end_frame_time: Instant,
     pub frame_num: u64,
+    // When another frame is needed without further input, e.g. for an
+    // animation. Reset every frame.
+    pub redraw_at: Option<Instant>,
 }
 
 impl Io {
@@ -50,24 +65,36 @@ impl Io {
             kbd_captured: None,
             has_mouse: None,
             has_kbd: None,
+            mods: ModifiersState::empty(),
+            keys_pressed: Vec::new(),
             is_mouse_pressed: false,
             mouse_pressed_pt: Pt2D::default(),
+            mouse_just_pressed: false,
             mouse_just_released: false,
             mouse_just_captured: false,
+            is_rmouse_pressed: false,
+            rmouse_just_pressed: false,
+            rmouse_just_released: false,
+            start_time: Instant::now(),
             begin_frame_time: Instant::now(),
             prev_begin_frame_time: Instant::now(),
             prev_end_frame_time: Instant::now(),
             frame_num: 0,
+            redraw_at: None,
             mouse_req: None,
             mouse_capture_req: None,
             mouse_scroll: Pt::zero(),
+            hover_since: Instant::now(),
+            menu_activated: false,
         }
     }
 
     pub fn begin(&mut self) {
         self.begin_frame_time = Instant::now();
         self.frame_num += 1;
+        self.redraw_at = None;
         self.mouse_delta = self.mouse_pt - self.prev_mouse_pt;
+        let prev_has_mouse = self.has_mouse.clone();
 
         // Try capture first, it takes precedence.
         if self.mouse_captured != self.has_mouse {
@@ -79,12 +106,28 @@ impl Io {
         // Check regular requests.
         let mouse_req = self.mouse_req.take().map(|f| f.1);
         self.has_mouse = self.has_mouse.take().or(mouse_req);
+        if self.has_mouse != prev_has_mouse {
+            self.hover_since = self.begin_frame_time;
+        }
     }
 
     pub fn end(&mut self) {
+        // Hover and capture are settled from this frame's requests at the
+        // start of the next frame, so draw another if they will change.
+        let capture = self.mouse_capture_req.as_ref().map(|r| &r.1);
+        let hover = capture.or(self.mouse_req.as_ref().map(|r| &r.1));
+        if capture != self.mouse_captured.as_ref() || hover != self.has_mouse.as_ref() {
+            self.request_redraw();
+        }
+
         self.mouse_just_captured = false;
+        self.mouse_just_pressed = false;
         self.mouse_just_released = false;
+        self.rmouse_just_pressed = false;
+        self.rmouse_just_released = false;
+        self.menu_activated = false;
         self.mouse_scroll = Pt::zero();
+        self.keys_pressed.clear();
         self.prev_mouse_pt = self.mouse_pt;
         self.prev_end_frame_time = Instant::now();
         self.prev_begin_frame_time = self.begin_frame_time;
@@ -113,6 +156,44 @@ impl Io {
         }
     }
 
+    // Asks for another frame straight after this one.
+    pub fn request_redraw(&mut self) {
+        self.request_redraw_at(self.begin_frame_time);
+    }
+
+    // Asks for a frame at |t|, or sooner if something else needs one.
+    pub fn request_redraw_at(&mut self, t: Instant) {
+        self.redraw_at = Some(self.redraw_at.map_or(t, |r| r.min(t)));
+    }
+
+    #[must_use]
+    pub fn key_pressed(&self, k: NamedKey) -> bool {
+        self.keys_pressed.contains(&Key::Named(k))
+    }
+
+    #[must_use]
+    pub fn char_pressed(&self, c: &str) -> bool {
+        self.keys_pressed
+            .iter()
+            .any(|k| matches!(k, Key::Character(s) if s.eq_ignore_ascii_case(c)))
+    }
+
+    // Text typed this frame, ignoring characters typed with a shortcut
+    // modifier held.
+    #[must_use]
+    pub fn typed(&self) -> String {
+        if self.mods.control_key() || self.mods.alt_key() || self.mods.super_key() {
+            return String::new();
+        }
+        self.keys_pressed
+            .iter()
+            .filter_map(|k| match k {
+                Key::Character(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn process_event(&mut self, w: &Window, e: &WindowEvent) {
         match *e {
             WindowEvent::Resized(ps) => self.scr_sz = ps.to_logical::<f64>(w.scale_factor()).into(),
@@ -123,14 +204,30 @@ impl Io {
                 MouseScrollDelta::LineDelta(x, y) => self.mouse_scroll += pt(x, -y),
                 MouseScrollDelta::PixelDelta(_) => {}
             },
-            WindowEvent::MouseInput { button, state, .. } => {
-                if button == MouseButton::Left {
+            WindowEvent::MouseInput { button, state, .. } => match button {
+                MouseButton::Left => {
+                    self.mouse_just_pressed =
+                        state == ElementState::Pressed && !self.is_mouse_pressed;
                     self.mouse_just_released =
                         state == ElementState::Released && self.is_mouse_pressed;
                     self.is_mouse_pressed = state == ElementState::Pressed;
                     self.mouse_pressed_pt = self.mouse_pt;
                 }
+                MouseButton::Right => {
+                    self.rmouse_just_pressed =
+                        state == ElementState::Pressed && !self.is_rmouse_pressed;
+                    self.rmouse_just_released =
+                        state == ElementState::Released && self.is_rmouse_pressed;
+                    self.is_rmouse_pressed = state == ElementState::Pressed;
+                }
+                _ => {}
+            },
+            WindowEvent::KeyboardInput { ref event, .. } => {
+                if event.state == ElementState::Pressed {
+                    self.keys_pressed.push(event.logical_key.clone());
+                }
             }
+            WindowEvent::ModifiersChanged(ref m) => self.mods = m.state(),
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 self.dp_to_px = scale_factor;
             }
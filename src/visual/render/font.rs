@@ -11,7 +11,8 @@ use crate::visual::render::atlas::AtlasHandle;
 use crate::visual::render::painter::{PaintCtx, Painter};
 use crate::visual::types::{GblRt, GblSz, Rt2D, grt, gsz, tpt};
 
-static FONT_DATA: &[u8] = include_bytes!("../../assets/OpenSans-Regular.ttf");
+pub static SANS_FONT: &[u8] = include_bytes!("../../assets/OpenSans-Regular.ttf");
+pub static MONO_FONT: &[u8] = include_bytes!("../../assets/ProggyVector-Regular.ttf");
 
 pub struct Font {
     _ft: ft::Library,
@@ -41,10 +42,10 @@ struct LayoutInfo {
 }
 
 impl Font {
-    pub fn new() -> Result<Self> {
+    pub fn new(data: &'static [u8]) -> Result<Self> {
         let freetype = ft::Library::init()?; // TODO: Need to pull this out for multiple fonts.
-        let render_face = freetype.new_memory_face(FONT_DATA.to_owned(), 0)?;
-        let layout_face = hb::Font::new(hb::Face::from_bytes(FONT_DATA, 0));
+        let render_face = freetype.new_memory_face(data.to_owned(), 0)?;
+        let layout_face = hb::Font::new(hb::Face::from_bytes(data, 0));
 
         Ok(Self { _ft: freetype, render_face, layout_face, m: HashMap::default() })
     }
@@ -129,14 +130,17 @@ impl Font {
         text: &str,
         dp: f64,
         l: &GblLayer,
+        clip: Option<GblRt>,
     ) -> Result<()> {
         let mut cursor = l.r.tl();
+        let mut pctx = PaintCtx::new().z(l.z.coerce());
+        pctx.clip = clip;
         let layout = self.layout_line(dp_to_px, text, dp)?;
         for LayoutGlyph { info, adv, off } in layout.glyphs {
             if let Ok(info) = self.ensure_glyph(p, dp_to_px, info.codepoint, layout.px_size) {
                 let bb = GblRt::ptsz(info.bb.tl() + cursor + off, info.bb.sz());
                 let layer = p.get_tex(info.tex).with_rect(bb);
-                p.tex(PaintCtx::new().z(l.z.coerce()), layer);
+                p.tex(pctx, layer);
             }
             cursor += adv;
         }
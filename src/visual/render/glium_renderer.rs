@@ -7,7 +7,9 @@ use glium::glutin::surface::WindowSurface;
 use glium::index::PrimitiveType;
 use glium::program::ProgramCreationInput;
 use glium::texture::{RawImage2d, Texture2d};
-use glium::uniforms::{AsUniformValue, UniformValue, Uniforms};
+use glium::uniforms::{
+    AsUniformValue, MagnifySamplerFilter, MinifySamplerFilter, UniformValue, Uniforms,
+};
 use glium::{
     Blend, Depth, DepthTest, Display, DrawParameters, Frame, IndexBuffer, Program, Surface,
     VertexBuffer, implement_vertex,
@@ -25,9 +27,10 @@ use rgb::ComponentBytes;
 use winit::window::Window;
 
 use crate::errors::StringErrorConversion;
-use crate::visual::render::painter::{PaintCtx, PaintOp, Painter};
-use crate::visual::render::texture::{TexId, TexStore};
-use crate::visual::types::{Col, GblZ, TexRt, TexSz, lsz, tpt};
+use crate::visual::gui::layer::GblTf;
+use crate::visual::render::painter::{Mesh, PaintCtx, PaintOp, Painter};
+use crate::visual::render::texture::{Filter, TexId, TexStore};
+use crate::visual::types::{Col, GblRt, GblZ, TexRt, TexSz, trt};
 
 const TOLERANCE: f32 = 0.1;
 
@@ -158,22 +161,26 @@ impl GliumRenderer {
     }
 
     fn update_textures(&mut self, dtx: &'_ mut DrawContext<'_>, ts: &mut TexStore) -> Result<()> {
-        // Load new textures.
+        // Load new textures and upload changed regions of existing ones.
         for (tid, tex) in ts.iter_mut() {
-            if tex.dirty {
-                let image = RawImage2d::from_raw_rgba(
-                    tex.data.as_slice().as_bytes().to_vec(),
-                    tex.sz.into(),
-                );
-                match self.texmap.get(tid) {
-                    None => {
-                        self.texmap.insert(*tid, Texture2d::new(dtx.disp, image)?);
-                    }
-                    Some(gltex) => {
-                        gltex.write(TexRt::ptsz(tpt(0, 0), tex.sz).into(), image);
-                    }
+            let Some(r) = tex.dirty.take() else {
+                continue;
+            };
+            match self.texmap.get(tid) {
+                None => {
+                    let image = RawImage2d::from_raw_rgba(
+                        tex.data.as_slice().as_bytes().to_vec(),
+                        tex.sz.into(),
+                    );
+                    self.texmap.insert(*tid, Texture2d::new(dtx.disp, image)?);
+                }
+                Some(gltex) => {
+                    let image = RawImage2d::from_raw_rgba(
+                        tex.pixels(r).as_slice().as_bytes().to_vec(),
+                        r.sz().into(),
+                    );
+                    gltex.write(r.into(), image);
                 }
-                tex.dirty = false;
             }
         }
         // Get rid of old textures.
@@ -181,22 +188,53 @@ impl GliumRenderer {
         Ok(())
     }
 
+    // Converts a clip rect in dp to a scissor rect in physical pixels, which
+    // has its origin at the bottom left.
+    fn scissor(win: &Window, clip: GblRt) -> TexRt {
+        let sf = win.scale_factor();
+        let h = win.inner_size().height as f64;
+        let (l, t) = ((clip.x * sf).max(0.0), (clip.y * sf).max(0.0));
+        let (r, b) = ((clip.r() * sf).max(l), (clip.b() * sf).max(t));
+        trt(l.floor(), (h - b).max(0.0).floor(), (r - l).ceil(), (b - t).ceil())
+    }
+
+    // Meshes are copied as they are, since tessellating them would lose the
+    // colour of each vertex.
+    fn add_mesh(geom: &mut VertexBuffers<Vertex, u16>, tf: GblTf, m: &Mesh) -> Result<()> {
+        let base = geom.vertices.len();
+        geom.vertices.extend(m.v.iter().map(|&(p, c)| {
+            let p = tf.pt(p);
+            Vertex { p: [p.x as f32, p.y as f32], c: c.into(), uv: Default::default() }
+        }));
+        for &i in &m.idx {
+            let i = u16::try_from(base + i as usize).map_err(|_| eyre!("mesh too large"))?;
+            geom.indices.push(i);
+        }
+        Ok(())
+    }
+
     fn render(&mut self, dtx: &'_ mut DrawContext<'_>, ops: &[(PaintCtx, PaintOp)]) -> Result<()> {
-        let mut geom_map: BTreeMap<(GblZ, Option<TexId>), VertexBuffers<Vertex, u16>> =
-            BTreeMap::new();
+        type GeomKey = (GblZ, Option<TexRt>, Option<(TexId, Filter)>);
+        let mut geom_map: BTreeMap<GeomKey, VertexBuffers<Vertex, u16>> = BTreeMap::new();
 
         let fopt = FillOptions::tolerance(TOLERANCE);
         for (pctx, op) in ops {
             let tf = pctx.tf;
             let z = tf.z(pctx.z);
+            let clip = pctx.clip.map(|c| Self::scissor(dtx.win, c));
 
-            let mut geom = geom_map.entry((z, None)).or_default();
+            let mut geom = geom_map.entry((z, clip, None)).or_default();
             if let PaintOp::Texture { tex } = op {
-                geom = geom_map.entry((z, Some(tex.tex))).or_default();
+                geom = geom_map.entry((z, clip, Some((tex.tex, tex.filter)))).or_default();
+            }
+            if let PaintOp::FillMesh { m } = op {
+                Self::add_mesh(geom, tf, m)?;
+                continue;
             }
             let mut buf = BuffersBuilder::new(geom, VertexCtor::new(pctx.col));
 
-            let line_width_px = pctx.line_width * dtx.win.scale_factor();
+            // Line widths scale with the transform, like everything else drawn.
+            let line_width_px = pctx.line_width * tf.avg_scale() * dtx.win.scale_factor();
             let sopt = StrokeOptions::tolerance(TOLERANCE).with_line_width(line_width_px as f32);
 
             let mut b = Path::builder();
@@ -205,10 +243,8 @@ impl GliumRenderer {
                     self.filler.tessellate_path(&tf.path(p.clone()), &fopt, &mut buf).serr()?;
                 }
                 PaintOp::FillCirc { center, radius } => {
-                    // TODO: Size conversion of radius may be different in different axes.
-                    self.filler
-                        .tessellate_circle(tf.pt(center).into(), radius as f32, &fopt, &mut buf)
-                        .serr()?;
+                    b.add_circle(center.into(), radius as f32, Winding::Positive);
+                    self.filler.tessellate_path(&tf.path(b.build()), &fopt, &mut buf).serr()?;
                 }
                 PaintOp::FillPoly { ref pts } => {
                     let points = &pts.iter().map(|v| tf.pt(*v).into()).collect::<Vec<_>>();
@@ -229,19 +265,13 @@ impl GliumRenderer {
                     self.filler.tessellate_path(&b.build(), &fopt, &mut buf).serr()?;
                 }
                 PaintOp::FillRt { r } => {
-                    self.filler.tessellate_rectangle(&tf.rt(r).into(), &fopt, &mut buf).serr()?;
+                    b.add_rectangle(&r.into(), Winding::Positive);
+                    self.filler.tessellate_path(&tf.path(b.build()), &fopt, &mut buf).serr()?;
                 }
                 PaintOp::FillRRt { r, radius } => {
-                    // TODO: Ignores scaling with different x and y scales.
-                    let radii = tf.sz(lsz(radius, radius));
-                    let radii = &BorderRadii {
-                        top_left: radii.w as f32,
-                        top_right: radii.w as f32,
-                        bottom_left: radii.w as f32,
-                        bottom_right: radii.w as f32,
-                    };
-                    b.add_rounded_rectangle(&tf.rt(r).into(), radii, Winding::Positive);
-                    self.filler.tessellate_path(&b.build(), &fopt, &mut buf).serr()?;
+                    let radii = &BorderRadii::new(radius as f32);
+                    b.add_rounded_rectangle(&r.into(), radii, Winding::Positive);
+                    self.filler.tessellate_path(&tf.path(b.build()), &fopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeLine { st, en } => {
                     b.add_line_segment(&LineSegment {
@@ -255,18 +285,12 @@ impl GliumRenderer {
                     self.stroker.tessellate_path(&tf.path(p.clone()), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeCirc { center, radius } => {
-                    self.stroker
-                        .tessellate_circle(tf.pt(center).into(), radius as f32, &sopt, &mut buf)
-                        .serr()?;
+                    b.add_circle(center.into(), radius as f32, Winding::Positive);
+                    self.stroker.tessellate_path(&tf.path(b.build()), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeEllipse { center, radii, rot } => {
-                    b.add_ellipse(
-                        tf.pt(center).into(),
-                        tf.sz(radii).into(),
-                        rot,
-                        Winding::Positive,
-                    );
-                    self.stroker.tessellate_path(&b.build(), &sopt, &mut buf).serr()?;
+                    b.add_ellipse(center.into(), radii.into(), rot, Winding::Positive);
+                    self.stroker.tessellate_path(&tf.path(b.build()), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokePoly { ref pts, is_closed } => {
                     let points = &pts.iter().map(|v| tf.pt(*v).into()).collect::<Vec<_>>();
@@ -287,19 +311,13 @@ impl GliumRenderer {
                     self.stroker.tessellate_path(&b.build(), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeRt { r } => {
-                    self.stroker.tessellate_rectangle(&tf.rt(r).into(), &sopt, &mut buf).serr()?;
+                    b.add_rectangle(&r.into(), Winding::Positive);
+                    self.stroker.tessellate_path(&tf.path(b.build()), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeRRt { r, radius } => {
-                    // TODO: Ignores scaling with different x and y scales.
-                    let radii = tf.sz(lsz(radius, radius));
-                    let radii = &BorderRadii {
-                        top_left: radii.w as f32,
-                        top_right: radii.w as f32,
-                        bottom_left: radii.w as f32,
-                        bottom_right: radii.w as f32,
-                    };
-                    b.add_rounded_rectangle(&tf.rt(r).into(), radii, Winding::Positive);
-                    self.stroker.tessellate_path(&b.build(), &sopt, &mut buf).serr()?;
+                    let radii = &BorderRadii::new(radius as f32);
+                    b.add_rounded_rectangle(&r.into(), radii, Winding::Positive);
+                    self.stroker.tessellate_path(&tf.path(b.build()), &sopt, &mut buf).serr()?;
                 }
                 PaintOp::StrokeTri { v } => {
                     self.stroker
@@ -317,6 +335,7 @@ impl GliumRenderer {
                         )
                         .serr()?;
                 }
+                PaintOp::FillMesh { .. } => {}
                 PaintOp::Texture { tex } => {
                     let mut b = Path::builder_with_attributes(2);
                     b.begin(tex.r.bl().into(), &tex.uv.tl().to_f32().to_arr());
@@ -335,18 +354,27 @@ impl GliumRenderer {
         let mut uni = UniformMap(HashMap::default());
         uni.add_val::<(f32, f32)>("screen_dp", (dp.w as f32, dp.h as f32));
 
-        for ((_, tex), geom) in &geom_map {
+        for ((_, clip, tex), geom) in &geom_map {
             let vertices = VertexBuffer::new(dtx.disp, &geom.vertices)?;
             let indices = IndexBuffer::new(dtx.disp, PrimitiveType::TrianglesList, &geom.indices)?;
             uni.add_val("use_tex", tex.is_some());
 
-            if let Some(tid) = tex {
-                uni.add_ref(
-                    "sampler",
-                    self.texmap.get(tid).ok_or_else(|| eyre!("unknown texture id"))?,
-                );
+            if let Some((tid, filter)) = tex {
+                let gltex = self.texmap.get(tid).ok_or_else(|| eyre!("unknown texture id"))?;
+                match filter {
+                    Filter::Linear => uni.add_ref("sampler", gltex),
+                    Filter::Nearest => uni.add_val(
+                        "sampler",
+                        gltex
+                            .sampled()
+                            .magnify_filter(MagnifySamplerFilter::Nearest)
+                            .minify_filter(MinifySamplerFilter::Nearest),
+                    ),
+                }
             }
-            dtx.frame.draw(&vertices, &indices, &self.prog, &uni, &dtx.draw_params)?;
+            let params =
+                DrawParameters { scissor: clip.map(Into::into), ..dtx.draw_params.clone() };
+            dtx.frame.draw(&vertices, &indices, &self.prog, &uni, &params)?;
         }
         Ok(())
     }
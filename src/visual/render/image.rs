@@ -0,0 +1,238 @@
+use std::fs;
+use std::path::Path;
+
+use eyre::{Result, eyre};
+use rgb::{ComponentBytes, FromSlice, RGBA8};
+
+use crate::visual::types::{TexSz, tsz};
+
+// File formats images can be read from and written to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Qoi,
+    // Binary PPM. Has no alpha channel, so it is dropped when writing.
+    Ppm,
+}
+
+impl ImageFormat {
+    // Format named by the extension of |p|.
+    pub fn from_path(p: impl AsRef<Path>) -> Result<Self> {
+        let p = p.as_ref();
+        let ext = p.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("png") => Ok(Self::Png),
+            Some("qoi") => Ok(Self::Qoi),
+            Some("ppm") => Ok(Self::Ppm),
+            _ => Err(eyre!("unknown image format for {}", p.display())),
+        }
+    }
+
+    // Format of encoded |bytes|, from their magic number.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(b"qoif") {
+            Some(Self::Qoi)
+        } else if bytes.starts_with(b"P6") {
+            Some(Self::Ppm)
+        } else {
+            None
+        }
+    }
+}
+
+// An RGBA image in memory, stored row by row from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub sz: TexSz,
+    pub data: Vec<RGBA8>,
+}
+
+impl RgbaImage {
+    pub fn new(sz: TexSz, data: Vec<RGBA8>) -> Result<Self> {
+        let n = sz.w as usize * sz.h as usize;
+        if data.len() != n {
+            return Err(eyre!("expected {n} pixels for {sz}, got {}", data.len()));
+        }
+        Ok(Self { sz, data })
+    }
+
+    // Decodes an image in any supported format.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        match ImageFormat::sniff(bytes) {
+            Some(ImageFormat::Png) => decode_png(bytes),
+            Some(ImageFormat::Qoi) => decode_qoi(bytes),
+            Some(ImageFormat::Ppm) => decode_ppm(bytes),
+            None => Err(eyre!("unrecognised image format")),
+        }
+    }
+
+    pub fn encode(&self, fmt: ImageFormat) -> Result<Vec<u8>> {
+        match fmt {
+            ImageFormat::Png => encode_png(self),
+            ImageFormat::Qoi => Ok(qoi::encode_to_vec(self.data.as_bytes(), self.sz.w, self.sz.h)?),
+            ImageFormat::Ppm => Ok(encode_ppm(self)),
+        }
+    }
+
+    pub fn load(p: impl AsRef<Path>) -> Result<Self> {
+        let p = p.as_ref();
+        let bytes = fs::read(p).map_err(|e| eyre!("could not read {}: {e}", p.display()))?;
+        Self::decode(&bytes).map_err(|e| eyre!("could not decode {}: {e}", p.display()))
+    }
+
+    // Writes the image in the format given by the extension of |p|.
+    pub fn save(&self, p: impl AsRef<Path>) -> Result<()> {
+        let p = p.as_ref();
+        let bytes = self.encode(ImageFormat::from_path(p)?)?;
+        fs::write(p, bytes).map_err(|e| eyre!("could not write {}: {e}", p.display()))
+    }
+}
+
+fn decode_png(bytes: &[u8]) -> Result<RgbaImage> {
+    let mut d = png::Decoder::new(bytes);
+    // Expand palettes, low bit depths and transparency chunks to 8 bits.
+    d.set_transformations(png::Transformations::normalize_to_color8());
+    let mut r = d.read_info()?;
+    let mut buf = vec![0; r.output_buffer_size()];
+    let info = r.next_frame(&mut buf)?;
+    let buf = &buf[..info.buffer_size()];
+    let data = match info.color_type {
+        png::ColorType::Rgba => buf.as_rgba().to_vec(),
+        png::ColorType::Rgb => buf.as_rgb().iter().map(|p| p.with_alpha(255)).collect(),
+        png::ColorType::GrayscaleAlpha => {
+            buf.chunks_exact(2).map(|p| RGBA8::new(p[0], p[0], p[0], p[1])).collect()
+        }
+        png::ColorType::Grayscale => buf.iter().map(|&v| RGBA8::new(v, v, v, 255)).collect(),
+        png::ColorType::Indexed => return Err(eyre!("indexed png was not expanded")),
+    };
+    RgbaImage::new(tsz(info.width, info.height), data)
+}
+
+fn encode_png(img: &RgbaImage) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut e = png::Encoder::new(&mut out, img.sz.w, img.sz.h);
+    e.set_color(png::ColorType::Rgba);
+    e.set_depth(png::BitDepth::Eight);
+    let mut w = e.write_header()?;
+    w.write_image_data(img.data.as_bytes())?;
+    w.finish()?;
+    Ok(out)
+}
+
+fn decode_qoi(bytes: &[u8]) -> Result<RgbaImage> {
+    let (h, buf) = qoi::decode_to_vec(bytes)?;
+    let data = match h.channels {
+        qoi::Channels::Rgba => buf.as_rgba().to_vec(),
+        qoi::Channels::Rgb => buf.as_rgb().iter().map(|p| p.with_alpha(255)).collect(),
+    };
+    RgbaImage::new(tsz(h.width, h.height), data)
+}
+
+// Splits the next header token off |bytes|, skipping whitespace and comments.
+fn ppm_token<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    loop {
+        match bytes.first() {
+            Some(c) if c.is_ascii_whitespace() => *bytes = &bytes[1..],
+            Some(b'#') => {
+                let end = bytes.iter().position(|&c| c == b'\n').unwrap_or(bytes.len());
+                *bytes = &bytes[end..];
+            }
+            Some(_) => break,
+            None => return Err(eyre!("truncated ppm header")),
+        }
+    }
+    let end = bytes.iter().position(u8::is_ascii_whitespace).unwrap_or(bytes.len());
+    let (tok, rest) = bytes.split_at(end);
+    *bytes = rest;
+    Ok(tok)
+}
+
+fn ppm_num(bytes: &mut &[u8]) -> Result<u32> {
+    let tok = ppm_token(bytes)?;
+    std::str::from_utf8(tok)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| eyre!("bad number in ppm header: {}", String::from_utf8_lossy(tok)))
+}
+
+fn decode_ppm(mut bytes: &[u8]) -> Result<RgbaImage> {
+    if ppm_token(&mut bytes)? != b"P6" {
+        return Err(eyre!("only binary ppm (P6) is supported"));
+    }
+    let (w, h, max) = (ppm_num(&mut bytes)?, ppm_num(&mut bytes)?, ppm_num(&mut bytes)?);
+    if max == 0 || max > 65535 {
+        return Err(eyre!("bad ppm max value {max}"));
+    }
+    // A single whitespace byte separates the header from the samples.
+    let bytes = bytes.get(1..).unwrap_or_default();
+    let bps = if max < 256 { 1 } else { 2 };
+    let len = (w as usize)
+        .checked_mul(h as usize)
+        .and_then(|n| n.checked_mul(3 * bps))
+        .ok_or_else(|| eyre!("ppm size {w}x{h} too large"))?;
+    let samples = bytes.get(..len).ok_or_else(|| eyre!("truncated ppm data"))?;
+    let scale = |s: &[u8]| {
+        let v = if bps == 1 { u32::from(s[0]) } else { (u32::from(s[0]) << 8) | u32::from(s[1]) };
+        (v.min(max) * 255 / max) as u8
+    };
+    let data = samples
+        .chunks_exact(3 * bps)
+        .map(|p| RGBA8::new(scale(&p[..bps]), scale(&p[bps..2 * bps]), scale(&p[2 * bps..]), 255))
+        .collect();
+    RgbaImage::new(tsz(w, h), data)
+}
+
+fn encode_ppm(img: &RgbaImage) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", img.sz.w, img.sz.h).into_bytes();
+    out.extend(img.data.iter().flat_map(|p| [p.r, p.g, p.b]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_image() -> RgbaImage {
+        let data = (0..6u8).map(|i| RGBA8::new(i * 40, 255 - i * 40, i, 255 - i)).collect();
+        RgbaImage::new(tsz(3, 2), data).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let img = test_image();
+        for fmt in [ImageFormat::Png, ImageFormat::Qoi] {
+            let bytes = img.encode(fmt)?;
+            assert_eq!(ImageFormat::sniff(&bytes), Some(fmt));
+            assert_eq!(RgbaImage::decode(&bytes)?, img);
+        }
+        let ppm = RgbaImage::decode(&img.encode(ImageFormat::Ppm)?)?;
+        assert_eq!(ppm.sz, img.sz);
+        assert!(ppm.data.iter().zip(&img.data).all(|(a, b)| a.rgb() == b.rgb() && a.a == 255));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_ppm() -> Result<()> {
+        let img = RgbaImage::decode(b"P6 # comment\n1 2\n# another\n15\n\x0f\x00\x05\x00\x0f\x0f")?;
+        assert_eq!(img.sz, tsz(1, 2));
+        assert_eq!(img.data, [RGBA8::new(255, 0, 85, 255), RGBA8::new(0, 255, 255, 255)]);
+        let wide = RgbaImage::decode(b"P6 1 1 65535 \xff\xff\x00\x00\x80\x00")?;
+        assert_eq!(wide.data, [RGBA8::new(255, 0, 127, 255)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(RgbaImage::decode(b"nonsense").is_err());
+        assert!(RgbaImage::decode(b"P6 2 2 255 \x00\x00").is_err());
+        assert!(RgbaImage::decode(b"P6 2 x 255 ").is_err());
+        assert!(RgbaImage::decode(b"P6 4294967295 4294967295 255 ").is_err());
+        assert!(RgbaImage::decode(b"\x89PNG\r\n\x1a\n\x00").is_err());
+        assert!(RgbaImage::new(tsz(2, 2), Vec::new()).is_err());
+        assert!(ImageFormat::from_path("a.bmp").is_err());
+        assert_eq!(ImageFormat::from_path("a/b.PNG").ok(), Some(ImageFormat::Png));
+    }
+}
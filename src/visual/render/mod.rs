@@ -1,5 +1,6 @@
 pub mod atlas;
 pub mod font;
 pub mod glium_renderer;
+pub mod image;
 pub mod painter;
 pub mod texture;
@@ -1,4 +1,4 @@
-use eyre::Result;
+use eyre::{Result, eyre};
 use lyon::math::Angle;
 use lyon::path::Path;
 use rgb::RGBA8;
@@ -7,11 +7,40 @@ use winit::window::CursorIcon;
 use crate::visual::colors::WHITE;
 use crate::visual::gui::layer::GblTf;
 use crate::visual::render::atlas::{Atlas, AtlasHandle};
-use crate::visual::render::texture::{TexStore, TextureLayer};
-use crate::visual::types::{Col, LclPt, LclRt, LclSz, LclZ, TexPt, TexSz, lz};
+use crate::visual::render::image::RgbaImage;
+use crate::visual::render::texture::{Tex, TexId, TexStore, TextureLayer};
+use crate::visual::types::{Col, GblPt, GblRt, LclPt, LclRt, LclSz, LclZ, TexPt, TexRt, TexSz, lz};
 
 pub type TexHandle = AtlasHandle;
 
+// Triangles with a colour at each vertex, blended across each triangle. Used
+// for gradients.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub v: Vec<(LclPt, Col)>,
+    pub idx: Vec<u32>,
+}
+
+impl Mesh {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds the quad with corners |v| and colours |c|, in order around it.
+    pub fn quad(&mut self, v: [LclPt; 4], c: [Col; 4]) {
+        let i = self.v.len() as u32;
+        self.v.extend(v.into_iter().zip(c));
+        self.idx.extend([i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+
+    // Adds |r| with colours at its top left, top right, bottom right and
+    // bottom left corners.
+    pub fn rt(&mut self, r: LclRt, c: [Col; 4]) {
+        self.quad([r.tl(), r.tr(), r.br(), r.bl()], c);
+    }
+}
+
 #[derive(Debug)]
 pub(super) enum PaintOp {
     FillPath { p: Path },
@@ -29,6 +58,7 @@ pub(super) enum PaintOp {
     StrokeRt { r: LclRt },
     StrokeRRt { r: LclRt, radius: f64 },
     StrokeTri { v: [LclPt; 3] },
+    FillMesh { m: Mesh },
     Texture { tex: TextureLayer },
 }
 
@@ -38,6 +68,7 @@ pub struct PaintCtx {
     pub z: LclZ,
     pub col: Col,
     pub line_width: f64,
+    pub clip: Option<GblRt>,
 }
 
 impl Default for PaintCtx {
@@ -49,7 +80,7 @@ impl Default for PaintCtx {
 impl PaintCtx {
     #[must_use]
     pub fn new() -> Self {
-        Self { line_width: 1.0, z: lz(0), col: WHITE, tf: GblTf::default() }
+        Self { line_width: 1.0, z: lz(0), col: WHITE, tf: GblTf::default(), clip: None }
     }
 
     #[must_use]
@@ -71,6 +102,17 @@ impl PaintCtx {
     pub fn line_width(self, line_width: f64) -> Self {
         Self { line_width, ..self }
     }
+
+    // Restricts drawing to |clip|, within any existing clip rect.
+    #[must_use]
+    pub fn clip(self, clip: GblRt) -> Self {
+        Self { clip: Some(self.clip.map_or(clip, |c| c.intersect(&clip))), ..self }
+    }
+
+    #[must_use]
+    pub fn contains(&self, p: GblPt) -> bool {
+        self.clip.is_none_or(|c| c.contains(p))
+    }
 }
 
 #[derive(Debug)]
@@ -154,6 +196,10 @@ impl Painter {
         self.ops.push((pctx, PaintOp::StrokeTri { v }));
     }
 
+    pub fn fill_mesh(&mut self, pctx: PaintCtx, m: Mesh) {
+        self.ops.push((pctx, PaintOp::FillMesh { m }));
+    }
+
     pub fn tex(&mut self, pctx: PaintCtx, tex: TextureLayer) {
         self.ops.push((pctx, PaintOp::Texture { tex }));
     }
@@ -173,6 +219,60 @@ impl Painter {
     pub fn write_px(&mut self, hnd: TexHandle, p: TexPt, c: RGBA8) {
         self.atlas.write_px(&mut self.ts, hnd, p, c);
     }
+
+    // Creates a texture of its own, outside the atlas, cleared to transparent.
+    pub fn create_tex(&mut self, sz: TexSz) -> TexId {
+        self.ts.insert(sz)
+    }
+
+    // Replaces all the pixels of texture |id| with |data|, given row by row
+    // from the top.
+    pub fn write_tex(&mut self, id: TexId, data: &[RGBA8]) -> Result<()> {
+        let sz = self.tex_sz(id)?;
+        self.write_tex_rect(id, TexRt::from_sz(sz), data)
+    }
+
+    // Replaces the pixels in |r| of texture |id|, with |r| and |data| given
+    // with rows from the top. Only |r| is uploaded again.
+    pub fn write_tex_rect(&mut self, id: TexId, r: TexRt, data: &[RGBA8]) -> Result<()> {
+        self.tex_sz(id)?;
+        self.ts.get_mut(id).write_rows(r, data)
+    }
+
+    pub fn tex_sz(&self, id: TexId) -> Result<TexSz> {
+        Ok(self.find_tex(id)?.sz)
+    }
+
+    pub fn create_tex_from(&mut self, img: &RgbaImage) -> Result<TexId> {
+        let id = self.create_tex(img.sz);
+        self.write_tex(id, &img.data)?;
+        Ok(id)
+    }
+
+    // Copies the pixels of texture |id| out, e.g. to save them.
+    pub fn read_tex(&self, id: TexId) -> Result<RgbaImage> {
+        let tex = self.find_tex(id)?;
+        let rows = tex.data.chunks_exact((tex.sz.w as usize).max(1)).rev();
+        RgbaImage::new(tex.sz, rows.flatten().copied().collect())
+    }
+
+    // Pixel at |p| of texture |id|, counting rows from the top.
+    pub fn tex_px(&self, id: TexId, p: TexPt) -> Result<Option<RGBA8>> {
+        Ok(self.find_tex(id)?.px(p))
+    }
+
+    // Changes whenever the pixels of texture |id| change.
+    pub fn tex_generation(&self, id: TexId) -> Result<u64> {
+        Ok(self.find_tex(id)?.generation)
+    }
+
+    pub fn free_tex(&mut self, id: TexId) {
+        self.ts.remove(id);
+    }
+
+    fn find_tex(&self, id: TexId) -> Result<&Tex> {
+        self.ts.get(id).ok_or_else(|| eyre!("unknown texture id {id}"))
+    }
 }
 
 impl Default for Painter {
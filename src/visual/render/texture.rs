@@ -2,38 +2,58 @@ use std::collections::hash_map::IterMut;
 
 use ahash::HashMap;
 use derive_more::Display;
+use eyre::{Result, eyre};
 use rgb::RGBA8;
 
-use crate::visual::types::{GblRt, TexPt, TexSz, TexUvRect};
+use crate::visual::types::{GblRt, TexPt, TexRt, TexSz, TexUvRect, tsz};
 
 pub type TexId = usize;
 
+// How a texture is sampled when drawn larger or smaller than its size.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Filter {
+    #[default]
+    Linear,
+    // Keeps pixels sharp, e.g. for pixel art or data.
+    Nearest,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct TextureLayer {
     pub r: GblRt,
     pub uv: TexUvRect,
     pub tex: TexId,
+    pub filter: Filter,
 }
 
 impl TextureLayer {
     #[must_use]
     pub fn new(r: GblRt, uv: TexUvRect, tex: TexId) -> Self {
-        Self { r, uv, tex }
+        Self { r, uv, tex, filter: Filter::Linear }
     }
 
     #[must_use]
     pub fn with_rect(self, r: GblRt) -> Self {
         Self { r, ..self }
     }
+
+    #[must_use]
+    pub fn with_filter(self, filter: Filter) -> Self {
+        Self { filter, ..self }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Display)]
-#[display("Tex[id:{id}, sz:{sz}, dirty:{dirty}]")]
+#[display("Tex[id:{id}, sz:{sz}, dirty:{dirty:?}]")]
 pub struct Tex {
     pub id: TexId,
     pub sz: TexSz,
+    // Pixels stored bottom row first, as uploaded.
     pub data: Vec<RGBA8>,
-    pub dirty: bool,
+    // Region of |data| changed since it was last uploaded, if any.
+    pub dirty: Option<TexRt>,
+    // Incremented whenever the pixels change.
+    pub generation: u64,
 }
 
 impl Tex {
@@ -42,12 +62,55 @@ impl Tex {
         let numpix: usize = sz.w as usize * sz.h as usize;
         let mut data = Vec::with_capacity(numpix);
         data.resize(numpix, RGBA8::new(0, 0, 0, 0));
-        Self { id, sz, data, dirty: true }
+        Self { id, sz, data, dirty: Some(TexRt::from_sz(sz)), generation: 0 }
+    }
+
+    fn mark(&mut self, r: TexRt) {
+        self.dirty = Some(self.dirty.map_or(r, |d| d.union(&r)));
+        self.generation += 1;
     }
 
     pub fn write(&mut self, p: TexPt, c: RGBA8) {
         self.data[(self.sz.w * p.y + p.x) as usize] = c;
-        self.dirty = true;
+        self.mark(TexRt::ptsz(p, tsz(1, 1)));
+    }
+
+    // Writes |data| into |r|, both with rows from the top of the image.
+    pub fn write_rows(&mut self, r: TexRt, data: &[RGBA8]) -> Result<()> {
+        if r.r() > self.sz.w || r.b() > self.sz.h {
+            return Err(eyre!("rect {r} outside {self}"));
+        }
+        let (w, h) = (r.w as usize, r.h as usize);
+        if data.len() != w * h {
+            return Err(eyre!("expected {} pixels for {r}, got {}", w * h, data.len()));
+        }
+        // Flip to storage order, which is bottom row first.
+        let r = TexRt::new(r.x, self.sz.h - r.b(), r.w, r.h);
+        for (i, row) in data.chunks_exact(w.max(1)).enumerate() {
+            let dst = (r.b() as usize - 1 - i) * self.sz.w as usize + r.x as usize;
+            self.data[dst..dst + w].copy_from_slice(row);
+        }
+        self.mark(r);
+        Ok(())
+    }
+
+    // Pixel at |p|, counting rows from the top of the image.
+    #[must_use]
+    pub fn px(&self, p: TexPt) -> Option<RGBA8> {
+        if p.x >= self.sz.w || p.y >= self.sz.h {
+            return None;
+        }
+        Some(self.data[((self.sz.h - 1 - p.y) * self.sz.w + p.x) as usize])
+    }
+
+    // Pixels in |r| of the stored data, bottom row first.
+    #[must_use]
+    pub fn pixels(&self, r: TexRt) -> Vec<RGBA8> {
+        let w = self.sz.w as usize;
+        (r.y as usize..r.b() as usize)
+            .flat_map(|y| &self.data[y * w + r.x as usize..y * w + r.r() as usize])
+            .copied()
+            .collect()
     }
 }
 
@@ -83,10 +146,19 @@ impl TexStore {
         id
     }
 
+    pub fn remove(&mut self, id: TexId) {
+        self.texs.remove(&id);
+    }
+
     pub fn get_mut(&mut self, id: TexId) -> &mut Tex {
         self.texs.get_mut(&id).expect("expected texture id")
     }
 
+    #[must_use]
+    pub fn get(&self, id: TexId) -> Option<&Tex> {
+        self.texs.get(&id)
+    }
+
     pub fn contains(&mut self, id: TexId) -> bool {
         self.texs.contains_key(&id)
     }
@@ -95,3 +167,28 @@ impl TexStore {
         self.texs.iter_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual::types::{tpt, trt};
+
+    #[test]
+    fn test_write_rows() {
+        let px = |v: u8| RGBA8::new(v, v, v, 255);
+        let mut t = Tex::new(1, tsz(3, 3));
+        t.dirty = None;
+        t.write_rows(trt(1, 0, 2, 2), &[px(1), px(2), px(3), px(4)]).unwrap();
+        // The top row of the image is the last row stored.
+        assert_eq!(t.pixels(trt(1, 2, 2, 1)), [px(1), px(2)]);
+        assert_eq!(t.pixels(trt(1, 1, 2, 1)), [px(3), px(4)]);
+        assert_eq!(t.dirty, Some(trt(1, 1, 2, 2)));
+        assert_eq!(t.px(tpt(2, 0)), Some(px(2)));
+        assert_eq!(t.px(tpt(3, 0)), None);
+
+        t.write(tpt(0, 0), px(5));
+        assert_eq!(t.dirty, Some(trt(0, 0, 3, 3)));
+        assert!(t.write_rows(trt(2, 2, 2, 2), &[px(0); 4]).is_err());
+        assert!(t.write_rows(trt(0, 0, 1, 1), &[]).is_err());
+    }
+}
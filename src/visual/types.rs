@@ -217,7 +217,20 @@ macro_rules! binop_vec2_scalar {
 }
 
 #[repr(C)]
-#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone, Display, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+    Display,
+    Serialize,
+    Deserialize,
+)]
 #[display("({x}, {y}, {w}, {h})")]
 pub struct Rt2D<T: Number, U> {
     pub x: T,
@@ -319,6 +332,24 @@ impl<T: Number, U: Basic> Rt2D<T, U> {
     pub fn contains(&self, p: Pt2D<T, U>) -> bool {
         p.x >= self.x && p.y >= self.y && p.x <= self.r() && p.y <= self.b()
     }
+
+    #[must_use]
+    pub fn intersect(&self, o: &Rt2D<T, U>) -> Rt2D<T, U> {
+        let max = |a: T, b: T| if a > b { a } else { b };
+        let min = |a: T, b: T| if a < b { a } else { b };
+        let (x, y) = (max(self.x, o.x), max(self.y, o.y));
+        let (r, b) = (min(self.r(), o.r()), min(self.b(), o.b()));
+        Rt2D::new(x, y, max(r, x) - x, max(b, y) - y)
+    }
+
+    // Smallest rect containing both rects.
+    #[must_use]
+    pub fn union(&self, o: &Rt2D<T, U>) -> Rt2D<T, U> {
+        let max = |a: T, b: T| if a > b { a } else { b };
+        let min = |a: T, b: T| if a < b { a } else { b };
+        let (x, y) = (min(self.x, o.x), min(self.y, o.y));
+        Rt2D::new(x, y, max(self.r(), o.r()) - x, max(self.b(), o.b()) - y)
+    }
 }
 
 impl<T: Number, U: Basic> From<Sz2D<T, U>> for Rt2D<T, U> {
@@ -691,6 +722,50 @@ pub fn lz<Z: ToPrimitive + Copy>(z: Z) -> LclZ {
     ZOrder::new(z.to_i32().unwrap())
 }
 
+// World space of a canvas, which is panned and zoomed onto local space.
+#[derive(
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+    Display,
+    From,
+    Serialize,
+    Deserialize,
+)]
+pub struct WldType;
+
+pub type WldRt = Rt2D<f64, WldType>;
+pub type WldPt = Pt2D<f64, WldType>;
+pub type WldSz = Sz2D<f64, WldType>;
+
+pub fn wrt<
+    X: ToPrimitive + Copy,
+    Y: ToPrimitive + Copy,
+    W: ToPrimitive + Copy,
+    H: ToPrimitive + Copy,
+>(
+    x: X,
+    y: Y,
+    w: W,
+    h: H,
+) -> WldRt {
+    Rt2D::new(x.to_f64().unwrap(), y.to_f64().unwrap(), w.to_f64().unwrap(), h.to_f64().unwrap())
+}
+
+pub fn wpt<X: ToPrimitive + Copy, Y: ToPrimitive + Copy>(x: X, y: Y) -> WldPt {
+    Pt2D::new(x.to_f64().unwrap(), y.to_f64().unwrap())
+}
+
+pub fn wsz<W: ToPrimitive + Copy, H: ToPrimitive + Copy>(w: W, h: H) -> WldSz {
+    Sz2D::new(w.to_f64().unwrap(), h.to_f64().unwrap())
+}
+
 #[derive(
     Debug,
     Default,
@@ -736,3 +811,5 @@ pub fn pt<X: ToPrimitive + Copy, Y: ToPrimitive + Copy>(x: X, y: Y) -> Pt {
 }
 
 pub const MAX_Z: LclZ = ZOrder::new(100000000);
+// Popups and modals are placed at or above this, over all regular windows.
+pub const POPUP_Z: LclZ = ZOrder::new(50000000);
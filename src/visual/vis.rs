@@ -2,7 +2,7 @@ use std::any::Any;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 
-use ahash::HashMap;
+use ahash::{HashMap, HashSet};
 use eyre::{Result, eyre};
 use serde::{Deserialize, Serialize};
 
@@ -12,11 +12,18 @@ use crate::visual::gui::layouts::layout::{Layout, LayoutInfo};
 use crate::visual::gui::layouts::resize_layout::{ResizeLayout, ResizeState};
 use crate::visual::gui::ui::Ui;
 use crate::visual::io::Io;
-use crate::visual::render::font::Font;
+use crate::visual::render::font::{Font, MONO_FONT, SANS_FONT};
 use crate::visual::render::painter::Painter;
-use crate::visual::types::{GblSz, LclSz};
+use crate::visual::render::texture::TexId;
+use crate::visual::types::{GblRt, GblSz, LclSz, TexSz};
 
 pub type FontId = u32;
+pub const SANS: FontId = 0;
+// Monospace font, e.g. for columns of numbers.
+pub const MONO: FontId = 1;
+
+// Id of the top level Ui, which lays out windows with a `ResizeLayout`.
+pub const ROOT_ID: &str = "top";
 
 #[typetag::serde]
 pub trait UserData: Any {
@@ -82,17 +89,33 @@ pub struct Vis {
     io: Io,
     mem: Memory,
     f: HashMap<FontId, Font>,
+    // Opened when first used. Kept open since on some platforms the copied
+    // text goes away with it.
+    clipboard: Option<arboard::Clipboard>,
+    // Textures owned by widgets with their size and a hash of what's in them,
+    // and the widgets which used theirs this frame.
+    wid_tex: HashMap<String, (TexId, TexSz, u64)>,
+    used_tex: HashSet<String>,
 }
 
 impl Vis {
     pub fn new(dp_to_px: f64, scr_sz: GblSz) -> Result<Self> {
         const VIS_PATH: &str = "vis.json";
         let mut f = HashMap::default();
-        f.insert(0, Font::new()?);
+        f.insert(SANS, Font::new(SANS_FONT)?);
+        f.insert(MONO, Font::new(MONO_FONT)?);
         let io = Io::new(dp_to_px, scr_sz);
         let mem = Memory::from_path(VIS_PATH);
         let p = Painter::new();
-        Ok(Self { p, io, mem, f })
+        Ok(Self {
+            p,
+            io,
+            mem,
+            f,
+            clipboard: None,
+            wid_tex: HashMap::default(),
+            used_tex: HashSet::default(),
+        })
     }
 
     #[must_use]
@@ -129,11 +152,21 @@ impl Vis {
         Ui::new(
             self,
             Layout::new(ResizeLayout::new(LayoutInfo::zero().hint(Hint::make_exact(scr_sz)))),
-            "top",
+            ROOT_ID,
         )
     }
 
     pub fn end(&mut self) {
+        // Free the textures of widgets which weren't drawn this frame.
+        let used = std::mem::take(&mut self.used_tex);
+        let p = &mut self.p;
+        self.wid_tex.retain(|id, (tex, _, _)| {
+            let keep = used.contains(id);
+            if !keep {
+                p.free_tex(*tex);
+            }
+            keep
+        });
         self.io_mut().end();
     }
 
@@ -141,18 +174,50 @@ impl Vis {
         self.mem().exit()
     }
 
+    // Texture owned by widget |id|, if any. It's freed at the end of a frame
+    // in which this isn't called for |id|.
+    pub fn wid_tex(&mut self, id: &str) -> Option<(TexId, TexSz, u64)> {
+        self.used_tex.insert(id.to_owned());
+        self.wid_tex.get(id).copied()
+    }
+
+    pub fn set_wid_tex(&mut self, id: &str, tex: (TexId, TexSz, u64)) {
+        self.used_tex.insert(id.to_owned());
+        self.wid_tex.insert(id.to_owned(), tex);
+    }
+
+    pub fn take_wid_tex(&mut self, id: &str) -> Option<(TexId, TexSz, u64)> {
+        self.wid_tex.remove(id)
+    }
+
+    pub fn copy_text(&mut self, text: &str) -> Result<()> {
+        let clipboard = match &mut self.clipboard {
+            Some(c) => c,
+            c => c.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+
     pub fn font(&mut self, id: FontId) -> &mut Font {
         self.f.get_mut(&id).unwrap()
     }
 
-    pub fn layout_text(&mut self, text: &str, dp: f64) -> Result<GblSz> {
-        let f = self.f.get_mut(&0).unwrap();
+    pub fn layout_text(&mut self, font: FontId, text: &str, dp: f64) -> Result<GblSz> {
+        let f = self.f.get_mut(&font).ok_or_else(|| eyre!("no font {font}"))?;
         f.layout(self.io.dp_to_px, text, dp)
     }
 
-    pub fn draw_text(&mut self, text: &str, dp: f64, l: &GblLayer) -> Result<()> {
-        let f = self.f.get_mut(&0).unwrap();
-        f.draw(&mut self.p, self.io.dp_to_px, text, dp, l)
+    pub fn draw_text(
+        &mut self,
+        font: FontId,
+        text: &str,
+        dp: f64,
+        l: &GblLayer,
+        clip: Option<GblRt>,
+    ) -> Result<()> {
+        let f = self.f.get_mut(&font).ok_or_else(|| eyre!("no font {font}"))?;
+        f.draw(&mut self.p, self.io.dp_to_px, text, dp, l, clip)
     }
 }
 